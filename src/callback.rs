@@ -0,0 +1,297 @@
+use crate::i18n::Language;
+use crate::rank_day::DATE_FORMAT;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use std::fmt;
+use teloxide::types::InlineKeyboardButton;
+
+/// Version of the layout of the callback data, changed when an action changes its fields
+const VERSION: &str = "v1";
+
+/// Biggest size of the callback data of a button, in bytes (limit of Telegram)
+pub const MAX_DATA_LENGTH: usize = 64;
+
+/// Errors of the callback data
+#[derive(Debug, PartialEq, Eq)]
+pub enum CallbackError {
+    /// The data was written by another version of the bot (ex: a button sent before an update)
+    UnknownVersion(String),
+    /// The action isn't known
+    UnknownAction(String),
+    /// A field of the action is missing or can't be read
+    InvalidField(String),
+    /// The data is bigger than `MAX_DATA_LENGTH`
+    TooLong(String),
+}
+
+impl fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallbackError::UnknownVersion(data) => write!(f, "unknown version of callback data {data:?}"),
+            CallbackError::UnknownAction(data) => write!(f, "unknown callback action {data:?}"),
+            CallbackError::InvalidField(data) => write!(f, "invalid field in callback data {data:?}"),
+            CallbackError::TooLong(data) => write!(f, "callback data {data:?} is longer than {MAX_DATA_LENGTH} bytes"),
+        }
+    }
+}
+
+impl std::error::Error for CallbackError {}
+
+/// Choice of the preview of an import
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImportChoice {
+    /// Import and replace the days already ranked
+    Replace,
+    /// Import only the days not ranked yet
+    Keep,
+    Cancel,
+}
+
+/// Action of a button of an inline keyboard, saved in its callback data
+///
+/// The data is the version, the action and its fields separated by `:` (ex: `v1:rank:42:3`).
+#[derive(Clone, PartialEq, Debug)]
+pub enum CallbackAction {
+    /// Rank a rank day
+    Rank { rank_day: i64, rank: u8 },
+    /// Clear the rank of a rank day to choose again
+    Edit { rank_day: i64 },
+    /// Wait a comment for a rank day
+    Comment { rank_day: i64 },
+    /// Delete the comment of a rank day
+    DeleteComment { rank_day: i64 },
+    /// Send the message to rank a past day of a tracker
+    Rate { tracker: i64, date: NaiveDate },
+    /// Set the timezone of the user
+    Timezone(Tz),
+    /// Confirm or cancel the import waiting in the chat
+    Import(ImportChoice),
+    /// Delete a tracker, or cancel the deletion if None
+    DeleteTracker { tracker: Option<i64> },
+    /// Delete the user and all his days, or cancel the deletion
+    DeleteAccount { confirm: bool },
+    /// Set the language of the user
+    Language(Language),
+    /// Send the broadcast previewed by an administrator of the bot, or cancel it
+    Broadcast { confirm: bool },
+}
+
+impl CallbackAction {
+    /// Write the action as callback data
+    ///
+    /// # Return
+    /// Return an error if the data is bigger than the limit of Telegram
+    pub fn to_data(&self) -> Result<String, CallbackError> {
+        let fields = match self {
+            CallbackAction::Rank { rank_day, rank } => format!("rank:{rank_day}:{rank}"),
+            CallbackAction::Edit { rank_day } => format!("edit:{rank_day}"),
+            CallbackAction::Comment { rank_day } => format!("comment:{rank_day}"),
+            CallbackAction::DeleteComment { rank_day } => format!("uncomment:{rank_day}"),
+            CallbackAction::Rate { tracker, date } => format!("rate:{tracker}:{}", date.format(DATE_FORMAT)),
+            CallbackAction::Timezone(timezone) => format!("tz:{}", timezone.name()),
+            CallbackAction::Import(choice) => match choice {
+                ImportChoice::Replace => "import:replace".to_string(),
+                ImportChoice::Keep => "import:keep".to_string(),
+                ImportChoice::Cancel => "import:cancel".to_string(),
+            },
+            CallbackAction::DeleteTracker { tracker } => match tracker {
+                Some(tracker) => format!("deltracker:{tracker}"),
+                None => "deltracker:cancel".to_string(),
+            },
+            CallbackAction::DeleteAccount { confirm } => match confirm {
+                true => "deleteme:confirm".to_string(),
+                false => "deleteme:cancel".to_string(),
+            },
+            CallbackAction::Language(language) => format!("lang:{}", language.code()),
+            CallbackAction::Broadcast { confirm } => match confirm {
+                true => "broadcast:send".to_string(),
+                false => "broadcast:cancel".to_string(),
+            },
+        };
+
+        let data = format!("{VERSION}:{fields}");
+        match data.len() <= MAX_DATA_LENGTH {
+            true => Ok(data),
+            false => Err(CallbackError::TooLong(data)),
+        }
+    }
+
+    /// Create a button doing the action
+    pub fn button(&self, label: impl Into<String>) -> Result<InlineKeyboardButton, CallbackError> {
+        Ok(InlineKeyboardButton::callback(label, self.to_data()?))
+    }
+
+    /// Read the callback data of a button
+    ///
+    /// # Return
+    /// Return an error if the data isn't written by this version of the bot or isn't valid
+    pub fn parse(data: &str) -> Result<CallbackAction, CallbackError> {
+        let fields = match data.split_once(':') {
+            Some((VERSION, fields)) => fields,
+            _ => return Err(CallbackError::UnknownVersion(data.to_string())),
+        };
+        let (action, fields) = fields.split_once(':').unwrap_or((fields, ""));
+        let invalid = || CallbackError::InvalidField(data.to_string());
+        let id = |field: &str| field.parse::<i64>().map_err(|_| invalid());
+
+        let action = match (action, fields.split_once(':')) {
+            ("rank", Some((rank_day, rank))) => CallbackAction::Rank {
+                rank_day: id(rank_day)?,
+                rank: rank.parse::<u8>().map_err(|_| invalid())?,
+            },
+            ("edit", None) => CallbackAction::Edit { rank_day: id(fields)? },
+            ("comment", None) => CallbackAction::Comment { rank_day: id(fields)? },
+            ("uncomment", None) => CallbackAction::DeleteComment { rank_day: id(fields)? },
+            ("rate", Some((tracker, date))) => CallbackAction::Rate {
+                tracker: id(tracker)?,
+                date: NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| invalid())?,
+            },
+            // The names of the timezones contain no ':'
+            ("tz", None) => CallbackAction::Timezone(fields.parse::<Tz>().map_err(|_| invalid())?),
+            ("import", None) => CallbackAction::Import(match fields {
+                "replace" => ImportChoice::Replace,
+                "keep" => ImportChoice::Keep,
+                "cancel" => ImportChoice::Cancel,
+                _ => return Err(invalid()),
+            }),
+            ("deltracker", None) => CallbackAction::DeleteTracker {
+                tracker: match fields {
+                    "cancel" => None,
+                    tracker => Some(id(tracker)?),
+                },
+            },
+            ("deleteme", None) => CallbackAction::DeleteAccount {
+                confirm: match fields {
+                    "confirm" => true,
+                    "cancel" => false,
+                    _ => return Err(invalid()),
+                },
+            },
+            ("lang", None) => CallbackAction::Language(Language::from_code(fields).ok_or_else(invalid)?),
+            ("broadcast", None) => CallbackAction::Broadcast {
+                confirm: match fields {
+                    "send" => true,
+                    "cancel" => false,
+                    _ => return Err(invalid()),
+                },
+            },
+            ("rank" | "edit" | "comment" | "uncomment" | "rate" | "tz" | "import" | "deltracker" | "deleteme" | "lang" | "broadcast", _) => {
+                return Err(invalid())
+            }
+            _ => return Err(CallbackError::UnknownAction(data.to_string())),
+        };
+        Ok(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::TZ_VARIANTS;
+
+    fn round_trip(action: CallbackAction) {
+        let data = action.to_data().unwrap();
+        assert!(data.starts_with("v1:"), "{data}");
+        assert_eq!(CallbackAction::parse(&data), Ok(action));
+    }
+
+    #[test]
+    fn actions_are_read_back() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        round_trip(CallbackAction::Rank { rank_day: 42, rank: 3 });
+        round_trip(CallbackAction::Rank { rank_day: 1, rank: 0 });
+        round_trip(CallbackAction::Edit { rank_day: 42 });
+        round_trip(CallbackAction::Comment { rank_day: 42 });
+        round_trip(CallbackAction::DeleteComment { rank_day: 42 });
+        round_trip(CallbackAction::Rate { tracker: 7, date });
+        round_trip(CallbackAction::Timezone(chrono_tz::Europe::Zurich));
+        round_trip(CallbackAction::Timezone(chrono_tz::UTC));
+        round_trip(CallbackAction::Import(ImportChoice::Replace));
+        round_trip(CallbackAction::Import(ImportChoice::Keep));
+        round_trip(CallbackAction::Import(ImportChoice::Cancel));
+        round_trip(CallbackAction::DeleteTracker { tracker: Some(7) });
+        round_trip(CallbackAction::DeleteTracker { tracker: None });
+        round_trip(CallbackAction::DeleteAccount { confirm: true });
+        round_trip(CallbackAction::DeleteAccount { confirm: false });
+        for language in Language::ALL {
+            round_trip(CallbackAction::Language(language));
+        }
+        round_trip(CallbackAction::Broadcast { confirm: true });
+        round_trip(CallbackAction::Broadcast { confirm: false });
+    }
+
+    #[test]
+    fn data_is_written() {
+        assert_eq!(CallbackAction::Rank { rank_day: 42, rank: 3 }.to_data().unwrap(), "v1:rank:42:3");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        assert_eq!(CallbackAction::Rate { tracker: 7, date }.to_data().unwrap(), "v1:rate:7:2024-01-05");
+        assert_eq!(CallbackAction::DeleteTracker { tracker: None }.to_data().unwrap(), "v1:deltracker:cancel");
+    }
+
+    #[test]
+    fn biggest_data_fits_in_the_limit() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let biggest = [
+            CallbackAction::Rank { rank_day: i64::MIN, rank: u8::MAX },
+            CallbackAction::Rate { tracker: i64::MIN, date },
+            CallbackAction::DeleteTracker { tracker: Some(i64::MIN) },
+        ];
+        for action in biggest {
+            let data = action.to_data().unwrap();
+            assert!(data.len() <= MAX_DATA_LENGTH, "{data}");
+            assert_eq!(CallbackAction::parse(&data), Ok(action));
+        }
+        for timezone in TZ_VARIANTS {
+            round_trip(CallbackAction::Timezone(timezone));
+        }
+    }
+
+    #[test]
+    fn too_long_error_keeps_the_data() {
+        let data = format!("v1:{}", "x".repeat(MAX_DATA_LENGTH));
+        let error = CallbackError::TooLong(data.clone());
+        assert!(error.to_string().contains(&data));
+        assert!(error.to_string().contains("64 bytes"));
+    }
+
+    #[test]
+    fn unknown_version_is_refused() {
+        for data in ["v0:rank:1:2", "v2:rank:1:2", "rank:1:2", "", "v1"] {
+            assert_eq!(CallbackAction::parse(data), Err(CallbackError::UnknownVersion(data.to_string())));
+        }
+    }
+
+    #[test]
+    fn unknown_action_is_refused() {
+        for data in ["v1:foo:1", "v1:", "v1:RANK:1:2", "v1:delete:1"] {
+            assert_eq!(CallbackAction::parse(data), Err(CallbackError::UnknownAction(data.to_string())));
+        }
+    }
+
+    #[test]
+    fn malformed_fields_are_refused() {
+        let malformed = [
+            "v1:rank:x:1",
+            "v1:rank:1",
+            "v1:rank:1:256",
+            "v1:rank:1:-1",
+            "v1:rank:1:2:3",
+            "v1:edit",
+            "v1:edit:1:2",
+            "v1:comment:",
+            "v1:uncomment:abc",
+            "v1:rate:1",
+            "v1:rate:1:2024-13-01",
+            "v1:rate:x:2024-01-01",
+            "v1:tz:Nowhere/City",
+            "v1:import:maybe",
+            "v1:deltracker:",
+            "v1:deleteme:yes",
+            "v1:lang:xx",
+            "v1:broadcast:",
+        ];
+        for data in malformed {
+            assert_eq!(CallbackAction::parse(data), Err(CallbackError::InvalidField(data.to_string())), "{data}");
+        }
+    }
+}
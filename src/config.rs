@@ -0,0 +1,440 @@
+use crate::scale::Scale;
+use crate::webhook::{WebhookConfig, WebhookError};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::net::SocketAddr;
+use std::path::Path;
+use teloxide::types::ChatId;
+
+/// Address listened by default for the updates sent by Telegram
+const DEFAULT_WEBHOOK_ADDRESS: &str = "0.0.0.0:8443";
+
+/// Settings of the configuration, with their environment variable
+///
+/// The environment variables replace the values of the configuration file.
+const SETTINGS: [(&str, &str); 10] = [
+    ("token", "TELOXIDE_TOKEN"),
+    ("path_database", "PATH_DATABASE"),
+    ("default_hour", "DEFAULT_HOUR"),
+    ("default_scale", "DEFAULT_SCALE"),
+    ("default_timezone", "DEFAULT_TIMEZONE"),
+    ("admins", "ADMIN_CHAT_IDS"),
+    ("webhook_url", "WEBHOOK_URL"),
+    ("webhook_address", "WEBHOOK_ADDRESS"),
+    ("webhook_secret", "WEBHOOK_SECRET"),
+    ("monitoring_address", "MONITORING_ADDRESS"),
+];
+
+lazy_static! {
+    /// Configuration of the bot, read once at startup
+    pub static ref CONFIG: Config = Config::load().unwrap_or_else(|e| panic!("Invalid configuration: {e}"));
+}
+
+/// Errors of the configuration
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The configuration file can't be read
+    File(String, String),
+    /// The configuration file isn't valid TOML, or has a setting unknown or of the wrong type
+    Syntax(String),
+    /// A setting needed isn't set
+    Missing(&'static str),
+    /// The value of a setting isn't valid
+    Invalid(&'static str, String),
+    /// The database can't be written
+    NotWritable(String, String),
+    Webhook(WebhookError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::File(path, e) => write!(f, "can't read the configuration file {path}: {e}"),
+            ConfigError::Syntax(e) => write!(f, "invalid configuration file: {e}"),
+            ConfigError::Missing(key) => write!(f, "{key} isn't set (${})", env_name(key)),
+            ConfigError::Invalid(key, value) => write!(f, "invalid {key} {value:?}"),
+            ConfigError::NotWritable(path, e) => write!(f, "the database {path} can't be written: {e}"),
+            ConfigError::Webhook(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<WebhookError> for ConfigError {
+    fn from(error: WebhookError) -> Self {
+        ConfigError::Webhook(error)
+    }
+}
+
+/// Name of the environment variable of a setting
+fn env_name(key: &str) -> &'static str {
+    SETTINGS.iter().find(|(name, _)| *name == key).map(|(_, var)| *var).unwrap_or_default()
+}
+
+/// Settings as written in the configuration file or in the environment, not checked yet
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    token: Option<String>,
+    path_database: Option<String>,
+    default_hour: Option<u8>,
+    default_scale: Option<String>,
+    default_timezone: Option<String>,
+    admins: Option<Vec<i64>>,
+    webhook_url: Option<String>,
+    webhook_address: Option<String>,
+    webhook_secret: Option<String>,
+    monitoring_address: Option<String>,
+}
+
+impl Settings {
+    /// Replace the settings by the environment variables set (and not empty)
+    ///
+    /// # Arguments
+    /// * `env` - Read an environment variable
+    ///
+    /// # Return
+    /// Return an error if a variable can't be converted to the type of its setting
+    fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |key: &str| env(env_name(key)).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+        let strings = [
+            ("token", &mut self.token),
+            ("path_database", &mut self.path_database),
+            ("default_scale", &mut self.default_scale),
+            ("default_timezone", &mut self.default_timezone),
+            ("webhook_url", &mut self.webhook_url),
+            ("webhook_address", &mut self.webhook_address),
+            ("webhook_secret", &mut self.webhook_secret),
+            ("monitoring_address", &mut self.monitoring_address),
+        ];
+        for (key, setting) in strings {
+            if let Some(value) = var(key) {
+                *setting = Some(value);
+            }
+        }
+        if let Some(hour) = var("default_hour") {
+            self.default_hour = Some(hour.parse::<u8>().map_err(|_| ConfigError::Invalid("default_hour", hour))?);
+        }
+        if let Some(admins) = var("admins") {
+            let admins = admins
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse::<i64>().map_err(|_| ConfigError::Invalid("admins", id.to_string())))
+                .collect::<Result<Vec<i64>, ConfigError>>()?;
+            self.admins = Some(admins);
+        }
+        Ok(())
+    }
+}
+
+/// Settings of the bot, read from the environment and from the optional file `$PATH_CONFIG`
+pub struct Config {
+    token_: Option<String>,
+    path_database_: String,
+    default_hour_: u8,
+    default_scale_: Scale,
+    default_timezone_: Tz,
+    admins_: Vec<ChatId>,
+    webhook_: Option<WebhookConfig>,
+    monitoring_address_: Option<SocketAddr>,
+}
+
+impl Config {
+    /// Read and check the configuration
+    ///
+    /// The file `$PATH_CONFIG` is read first if it is set, then the environment variables
+    /// replace its values.
+    ///
+    /// # Return
+    /// Return an error if a setting is missing or isn't valid, or if the database can't be written
+    pub fn load() -> Result<Config, ConfigError> {
+        let file = match env::var("PATH_CONFIG") {
+            Ok(path) => Some(fs::read_to_string(&path).map_err(|e| ConfigError::File(path, e.to_string()))?),
+            Err(_) => None,
+        };
+        let config = Config::from_sources(file.as_deref(), |var| env::var(var).ok())?;
+        check_writable(&config.path_database_)?;
+        Ok(config)
+    }
+
+    /// Read the settings of the configuration file and of the environment, and check them
+    ///
+    /// # Arguments
+    /// * `file` - The text of the configuration file, if there is one
+    /// * `env` - Read an environment variable, its value replaces the one of the file
+    ///
+    /// # Return
+    /// Return an error if a setting is missing or isn't valid
+    fn from_sources(file: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<Config, ConfigError> {
+        let mut settings = match file {
+            Some(text) => toml::from_str::<Settings>(text).map_err(|e| ConfigError::Syntax(e.to_string()))?,
+            None => Settings::default(),
+        };
+        settings.apply_env(env)?;
+        Config::from_settings(settings)
+    }
+
+    /// Check the settings and convert them to their type
+    fn from_settings(settings: Settings) -> Result<Config, ConfigError> {
+        let value = |setting: &Option<String>| setting.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(String::from);
+
+        let path_database = value(&settings.path_database).ok_or(ConfigError::Missing("path_database"))?;
+
+        let default_hour = match settings.default_hour {
+            Some(hour) if hour >= 24 => return Err(ConfigError::Invalid("default_hour", hour.to_string())),
+            Some(hour) => hour,
+            None => 22,
+        };
+        let default_scale = match value(&settings.default_scale) {
+            Some(scale) => Scale::parse(&scale).ok_or(ConfigError::Invalid("default_scale", scale))?,
+            None => Scale::default(),
+        };
+        let default_timezone = match value(&settings.default_timezone) {
+            Some(timezone) => timezone
+                .parse::<Tz>()
+                .map_err(|_| ConfigError::Invalid("default_timezone", timezone))?,
+            None => Tz::UTC,
+        };
+        let admins = settings.admins.unwrap_or_default().into_iter().map(ChatId).collect();
+
+        let webhook = match value(&settings.webhook_url) {
+            Some(url) => {
+                let address = value(&settings.webhook_address).unwrap_or(DEFAULT_WEBHOOK_ADDRESS.to_string());
+                let secret = value(&settings.webhook_secret).ok_or(WebhookError::MissingSecret)?;
+                Some(WebhookConfig::new(&address, &url, secret)?)
+            }
+            None => None,
+        };
+        let monitoring_address = value(&settings.monitoring_address)
+            .map(|address| {
+                address
+                    .parse::<SocketAddr>()
+                    .map_err(|_| ConfigError::Invalid("monitoring_address", address))
+            })
+            .transpose()?;
+
+        Ok(Config {
+            token_: value(&settings.token),
+            path_database_: path_database,
+            default_hour_: default_hour,
+            default_scale_: default_scale,
+            default_timezone_: default_timezone,
+            admins_: admins,
+            webhook_: webhook,
+            monitoring_address_: monitoring_address,
+        })
+    }
+
+    /// Token of the bot, only needed to start it (not to check the migrations)
+    pub fn get_token(&self) -> Option<String> {
+        self.token_.clone()
+    }
+
+    pub fn get_path_database(&self) -> String {
+        self.path_database_.clone()
+    }
+
+    /// Hour of the reminder of a new tracker
+    pub fn get_default_hour(&self) -> u8 {
+        self.default_hour_
+    }
+
+    /// Ranks of a new tracker
+    pub fn get_default_scale(&self) -> Scale {
+        self.default_scale_.clone()
+    }
+
+    /// Timezone of a new user
+    pub fn get_default_timezone(&self) -> Tz {
+        self.default_timezone_
+    }
+
+    /// Chats allowed to use the commands operating the bot
+    pub fn get_admins(&self) -> Vec<ChatId> {
+        self.admins_.clone()
+    }
+
+    /// Webhook receiving the updates, None to use long polling
+    pub fn get_webhook(&self) -> Option<WebhookConfig> {
+        self.webhook_.clone()
+    }
+
+    /// Address of the server of `/healthz` and `/metrics`, None to not start it
+    pub fn get_monitoring_address(&self) -> Option<SocketAddr> {
+        self.monitoring_address_
+    }
+}
+
+/// Check the database can be written, without creating it
+///
+/// A database which doesn't exist yet is created at startup, so its directory must be writable.
+fn check_writable(path: &str) -> Result<(), ConfigError> {
+    let not_writable = |e: String| ConfigError::NotWritable(path.to_string(), e);
+    let file = Path::new(path);
+    if file.exists() {
+        return OpenOptions::new()
+            .write(true)
+            .open(file)
+            .map(|_| ())
+            .map_err(|e| not_writable(e.to_string()));
+    }
+
+    let directory = match file.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    let metadata = fs::metadata(directory).map_err(|e| not_writable(format!("{}: {e}", directory.display())))?;
+    match metadata.is_dir() && !metadata.permissions().readonly() {
+        true => Ok(()),
+        false => Err(not_writable(format!("{} isn't a writable directory", directory.display()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read the environment variables from a list
+    fn env(vars: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars.to_vec();
+        move |name| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+    }
+
+    fn load(file: Option<&str>, vars: &[(&'static str, &'static str)]) -> Config {
+        Config::from_sources(file, env(vars)).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn error(file: Option<&str>, vars: &[(&'static str, &'static str)]) -> ConfigError {
+        match Config::from_sources(file, env(vars)) {
+            Ok(_) => panic!("the configuration should be invalid"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn file_is_read() {
+        let file = r#"
+            # Configuration of the bot
+            token = "123:abc"
+            path_database = "/data/picole.db"
+            default_hour = 21
+            default_scale = "1-10"
+            default_timezone = "Europe/Zurich"
+            admins = [1234, -5678]
+            monitoring_address = "127.0.0.1:9090"
+        "#;
+        let config = load(Some(file), &[]);
+        assert_eq!(config.get_token().as_deref(), Some("123:abc"));
+        assert_eq!(config.get_path_database(), "/data/picole.db");
+        assert_eq!(config.get_default_hour(), 21);
+        assert_eq!(config.get_default_scale().to_string(), "1-10");
+        assert_eq!(config.get_default_timezone(), chrono_tz::Europe::Zurich);
+        assert_eq!(config.get_admins(), vec![ChatId(1234), ChatId(-5678)]);
+        assert_eq!(config.get_monitoring_address(), Some("127.0.0.1:9090".parse().unwrap()));
+        assert!(config.get_webhook().is_none());
+    }
+
+    #[test]
+    fn defaults_are_used() {
+        let config = load(None, &[("PATH_DATABASE", "picole.db")]);
+        assert_eq!(config.get_token(), None);
+        assert_eq!(config.get_default_hour(), 22);
+        assert_eq!(config.get_default_scale().to_string(), Scale::default().to_string());
+        assert_eq!(config.get_default_timezone(), Tz::UTC);
+        assert!(config.get_admins().is_empty());
+        assert_eq!(config.get_monitoring_address(), None);
+    }
+
+    #[test]
+    fn environment_replaces_file() {
+        let file = r#"
+            path_database = "/data/picole.db"
+            default_hour = 21
+            default_timezone = "Europe/Zurich"
+            admins = [1234]
+        "#;
+        let vars = [
+            ("PATH_DATABASE", "/tmp/picole.db"),
+            ("DEFAULT_HOUR", "8"),
+            ("ADMIN_CHAT_IDS", " 1, 2 ,"),
+            // An empty variable doesn't replace the file
+            ("DEFAULT_TIMEZONE", " "),
+        ];
+        let config = load(Some(file), &vars);
+        assert_eq!(config.get_path_database(), "/tmp/picole.db");
+        assert_eq!(config.get_default_hour(), 8);
+        assert_eq!(config.get_admins(), vec![ChatId(1), ChatId(2)]);
+        assert_eq!(config.get_default_timezone(), chrono_tz::Europe::Zurich);
+    }
+
+    #[test]
+    fn database_is_needed() {
+        assert_eq!(error(None, &[]), ConfigError::Missing("path_database"));
+        assert_eq!(error(Some("path_database = \"\""), &[]), ConfigError::Missing("path_database"));
+        assert_eq!(error(None, &[]).to_string(), "path_database isn't set ($PATH_DATABASE)");
+    }
+
+    #[test]
+    fn invalid_values_are_refused() {
+        let database = ("PATH_DATABASE", "picole.db");
+        let cases = [
+            (("DEFAULT_HOUR", "24"), ConfigError::Invalid("default_hour", "24".to_string())),
+            (("DEFAULT_HOUR", "ten"), ConfigError::Invalid("default_hour", "ten".to_string())),
+            (("DEFAULT_SCALE", "3-1"), ConfigError::Invalid("default_scale", "3-1".to_string())),
+            (("DEFAULT_TIMEZONE", "Mars/Olympus"), ConfigError::Invalid("default_timezone", "Mars/Olympus".to_string())),
+            (("ADMIN_CHAT_IDS", "12,abc"), ConfigError::Invalid("admins", "abc".to_string())),
+            (("MONITORING_ADDRESS", "nowhere"), ConfigError::Invalid("monitoring_address", "nowhere".to_string())),
+        ];
+        for (var, expected) in cases {
+            assert_eq!(error(None, &[database, var]), expected);
+        }
+        let file = "path_database = \"picole.db\"\ndefault_hour = 30";
+        assert_eq!(error(Some(file), &[]), ConfigError::Invalid("default_hour", "30".to_string()));
+    }
+
+    #[test]
+    fn invalid_file_is_refused() {
+        let files = [
+            "path_database = \"picole.db\"\nunknown = 1",
+            "path_database = \"picole.db\"\ndefault_hour = \"21\"",
+            "path_database = \"picole.db\"\nadmins = \"1234\"",
+            "path_database = \"picole.db\"\ndefault_hour = -1",
+            "path_database = picole.db",
+        ];
+        for file in files {
+            assert!(matches!(error(Some(file), &[]), ConfigError::Syntax(_)), "{file}");
+        }
+    }
+
+    #[test]
+    fn webhook_needs_a_secret() {
+        let vars = [("PATH_DATABASE", "picole.db"), ("WEBHOOK_URL", "https://bot.example.com/telegram")];
+        assert_eq!(error(None, &vars), ConfigError::Webhook(WebhookError::MissingSecret));
+
+        let vars = [
+            ("PATH_DATABASE", "picole.db"),
+            ("WEBHOOK_URL", "https://bot.example.com/telegram"),
+            ("WEBHOOK_SECRET", "s3cret"),
+        ];
+        assert!(load(None, &vars).get_webhook().is_some());
+    }
+
+    #[test]
+    fn writable_check_creates_nothing() {
+        let directory = env::temp_dir();
+        let path = directory.join(format!("picole-check-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(check_writable(path), Ok(()));
+        assert!(!Path::new(path).exists());
+
+        let missing = directory.join("picole-missing-directory").join("picole.db");
+        assert!(matches!(check_writable(missing.to_str().unwrap()), Err(ConfigError::NotWritable(_, _))));
+    }
+}
@@ -1,299 +1,1178 @@
-use std::ops::Add;
-use crate::rank_day::RankDay;
+use crate::i18n::Language;
+use crate::import::ImportRow;
+use crate::metrics::DbTimer;
+use crate::migration::{self, Migration};
+use crate::rank_day::{RankDay, DATE_FORMAT};
+use crate::scale::Scale;
+use crate::schedule;
+use crate::tracker::Tracker;
 use crate::user::User;
-use chrono::{DateTime, Utc};
-use sqlx::ConnectOptions;
-use sqlx::{Connection, Executor, Row, SqliteConnection, Statement};
-use std::str::FromStr;
-use sqlx::sqlite::SqliteConnectOptions;
-use teloxide::types::{ChatId, MessageId};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::fmt;
+use std::time::Duration;
+use teloxide::types::{ChatId, MessageId, UserId};
 
+/// Time waited for a lock on the database before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Errors of the database layer
+#[derive(Debug)]
+pub enum DbError {
+    /// Error of SQLite or of the connection pool
+    Sqlx(sqlx::Error),
+    /// No user is registered for this chat
+    UserNotFound(ChatId),
+    /// No tracker has this id
+    TrackerNotFound(i64),
+    /// A row contains a value which can't be read (ex: a malformed date)
+    InvalidData(String),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Sqlx(e) => write!(f, "database error: {e}"),
+            DbError::UserNotFound(chat_id) => write!(f, "no user for chat {chat_id}"),
+            DbError::TrackerNotFound(id) => write!(f, "no tracker {id}"),
+            DbError::InvalidData(data) => write!(f, "invalid data in database: {data}"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DbError::Sqlx(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for DbError {
+    fn from(e: sqlx::Error) -> Self {
+        DbError::Sqlx(e)
+    }
+}
+
+/// Convert a timestamp read from the database
+fn from_timestamp(tst: i64) -> Result<DateTime<Utc>, DbError> {
+    DateTime::from_timestamp(tst, 0).ok_or_else(|| DbError::InvalidData(format!("timestamp {tst}")))
+}
+
+/// Convert a date read from the database
+fn parse_date(date: &str) -> Result<NaiveDate, DbError> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| DbError::InvalidData(format!("date {date}")))
+}
+
+/// Columns of a tracker and of its user, read by `row_to_tracker`
+const TRACKER_COLUMNS: &str = "User.chat_id, User.telegram_id, User.username, User.display_name, User.timezone,
+                               User.language, User.paused_until, User.stopped, User.send_failure, User.recap_weekly, User.recap_monthly,
+                               Tracker.id, Tracker.name, Tracker.question, Tracker.scale,
+                               Tracker.hour, Tracker.minute, Tracker.nudge_hour, Tracker.nudge_minute,
+                               Tracker.last_prompt, Tracker.last_nudge";
+
+/// Columns of a rank day, read by `row_to_rank_day`
+const RANK_DAY_COLUMNS: &str = "Rank_day.id, Rank_day.time, Rank_day.date, Rank_day.id_msg, Rank_day.rank,
+                                Rank_day.comment, Rank_day.rated_at";
+
+/// Number of users of the bot, shown by /users
+#[derive(Default)]
+pub struct UserCounts {
+    pub users: i64,
+    /// Users who ranked a day on their local date of today
+    pub active: i64,
+    /// Users with paused reminders
+    pub paused: i64,
+    /// Users with stopped reminders, or who can't receive messages anymore
+    pub stopped: i64,
+}
 
 pub struct Database {
-    path_: String,
+    pool_: SqlitePool,
 }
 
 impl Database {
+    /// Create the pool of connections, the connections are opened when needed
+    ///
+    /// The database uses the WAL journal, so the readers don't block the writer.
     pub fn new(path: String) -> Database {
-        let mut p = "sqlite:".to_string();
-        p = p.add(&*path);
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(BUSY_TIMEOUT);
         Database {
-            path_: p
+            pool_: SqlitePoolOptions::new().connect_lazy_with(options),
         }
     }
 
-    pub async fn create_table(&self) {
-        let mut conn = SqliteConnectOptions::from_str(self.path_.as_str())
-            .expect("Failed to create database")
-            .create_if_missing(true)
-            .connect()
-            .await
-            .expect("Failed to open connection");
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS User (\
-                        id INTEGER CONSTRAINT user_pk PRIMARY KEY AUTOINCREMENT,\
-                        chat_id INTEGER(8) NOT NULL CONSTRAINT user_chat_id UNIQUE,\
-                        username TEXT NOT NULL,\
-                        hour INTEGER(1) NOT NULL DEFAULT 22)"
-        ).await.unwrap();
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS Rank_day (\
-                        id INTEGER CONSTRAINT rank_day_pk PRIMARY KEY AUTOINCREMENT,\
-                        user_id INTEGER NOT NULL CONSTRAINT User_id_fk REFERENCES User (id),\
-                        time INTEGER(8) NOT NULL,\
-                        id_msg INTEGER(4),\
-                        rank INTEGER(1), \
-                        comment TEXT)"
-        ).await.unwrap();
-        conn.close();
-    }
-
-    pub async fn add_user(&self, user: User) -> bool {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
-
-        let stmt = conn
-            .prepare("SELECT id, username FROM User WHERE chat_id = ?")
-            .await
-            .unwrap();
-
-        let query = stmt
-            .query()
-            .bind(user.get_chat_id().0);
-
-        let result = query.fetch_optional(&mut conn).await.unwrap();
-
-        let user_exist;
+    /// Create the database if missing and apply the migrations not applied yet
+    ///
+    /// # Arguments
+    /// * `dry_run` - If true, the migrations are checked but not saved
+    ///
+    /// # Return
+    /// Return the migrations applied
+    pub async fn migrate(&self, dry_run: bool) -> Result<Vec<&'static Migration>, DbError> {
+        let mut conn = self.pool_.acquire().await?;
+        Ok(migration::run(&mut conn, dry_run).await?)
+    }
+
+    /// Check the database answers, for the health check
+    pub async fn ping(&self) -> Result<(), DbError> {
+        let _timer = DbTimer::start("ping");
+        sqlx::query("SELECT 1").execute(&self.pool_).await?;
+        Ok(())
+    }
+
+    /// Add a user, or update his names if he already exists
+    ///
+    /// A user is registered by his chat, which is his Telegram id for a private chat.
+    ///
+    /// # Return
+    /// Return true if the user already existed
+    pub async fn add_user(&self, user: User) -> Result<bool, DbError> {
+        let _timer = DbTimer::start("add_user");
+        let result = sqlx::query("SELECT id FROM User WHERE chat_id = ?")
+            .bind(user.get_chat_id().0)
+            .fetch_optional(&self.pool_)
+            .await?;
+
         match result {
             None => {
                 // add user
-                user_exist = false;
-                let stmt = conn
-                    .prepare("INSERT INTO User (chat_id, username, hour) VALUES (?, ?, ?)")
-                    .await
-                    .unwrap();
-
-                let query = stmt
-                    .query()
+                sqlx::query("INSERT INTO User (chat_id, telegram_id, username, display_name, timezone, language)
+                                   VALUES (?, ?, ?, ?, ?, ?)")
                     .bind(user.get_chat_id().0)
+                    .bind(user.get_telegram_id().map(|id| id.0 as i64))
                     .bind(user.get_username())
-                    .bind(user.get_hour());
-
-                query
-                    .execute(&mut conn)
-                    .await
-                    .expect("Error when inserting new user");
+                    .bind(user.get_display_name())
+                    .bind(user.get_timezone().name())
+                    .bind(user.get_language().code())
+                    .execute(&self.pool_)
+                    .await?;
+                Ok(false)
             }
             Some(row) => {
-                // modify username
-                user_exist = true;
-                let stmt = conn
-                    .prepare("UPDATE User SET username=? WHERE id=?")
-                    .await
-                    .unwrap();
-                let id: i64 = row.try_get("id").unwrap();
-                let query = stmt.query().bind(user.get_username()).bind(id);
-                query
-                    .execute(&mut conn)
-                    .await
-                    .expect("Error when updating user");
+                // modify names
+                let id: i64 = row.try_get("id")?;
+                sqlx::query("UPDATE User SET telegram_id=COALESCE(?, telegram_id), username=?, display_name=? WHERE id=?")
+                    .bind(user.get_telegram_id().map(|id| id.0 as i64))
+                    .bind(user.get_username())
+                    .bind(user.get_display_name())
+                    .bind(id)
+                    .execute(&self.pool_)
+                    .await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Update the username and the display name of a user, nothing is done if the chat isn't registered
+    ///
+    /// # Arguments
+    /// * `user` - The user with his current names
+    pub async fn refresh_user(&self, user: &User) -> Result<(), DbError> {
+        let _timer = DbTimer::start("refresh_user");
+        sqlx::query("UPDATE User SET username=?, display_name=?
+                     WHERE chat_id=? AND (username IS NOT ? OR display_name IS NOT ?)")
+            .bind(user.get_username())
+            .bind(user.get_display_name())
+            .bind(user.get_chat_id().0)
+            .bind(user.get_username())
+            .bind(user.get_display_name())
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a user with all his trackers and days, and his ranks as a member of the groups
+    pub async fn delete_user(&self, chat_id: ChatId) -> Result<(), DbError> {
+        let _timer = DbTimer::start("delete_user");
+        let user_id = self.get_user_id_by_chat_id(chat_id).await?;
+        let mut tx = self.pool_.begin().await?;
+        sqlx::query("DELETE FROM Group_rank
+                     WHERE rank_day_id IN (SELECT id FROM Rank_day WHERE user_id=?)")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        // The Telegram id of a private chat is its chat id, a group is never a member
+        sqlx::query("DELETE FROM Group_rank
+                     WHERE member_id = (SELECT COALESCE(telegram_id, CASE WHEN chat_id > 0 THEN chat_id END)
+                                        FROM User WHERE id=?)")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM Rank_day WHERE user_id=?")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM Tracker WHERE user_id=?")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM User WHERE id=?")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Count the users, the users active and the users paused, on the local date of each user
+    ///
+    /// # Arguments
+    /// * `now` - The actual time, the users who ranked a day on their local date of today are active
+    pub async fn get_user_counts(&self, now: DateTime<Utc>) -> Result<UserCounts, DbError> {
+        let _timer = DbTimer::start("get_user_counts");
+        let rows = sqlx::query("SELECT timezone, stopped, paused_until,
+                                       MAX(COALESCE((SELECT MAX(rated_at) FROM Rank_day WHERE Rank_day.user_id = User.id), 0),
+                                           COALESCE((SELECT MAX(Group_rank.rated_at) FROM Group_rank
+                                                     JOIN Rank_day ON Rank_day.id = Group_rank.rank_day_id
+                                                     WHERE Rank_day.user_id = User.id), 0)) AS last_rated_at
+                                FROM User")
+            .fetch_all(&self.pool_)
+            .await?;
+
+        let mut counts = UserCounts::default();
+        for row in rows {
+            let timezone: String = row.try_get("timezone")?;
+            let timezone = timezone
+                .parse::<Tz>()
+                .map_err(|_| DbError::InvalidData(format!("timezone {timezone}")))?;
+            let today = now.with_timezone(&timezone).date_naive();
+            let stopped: bool = row.try_get("stopped")?;
+            let paused_until: Option<String> = row.try_get("paused_until")?;
+            let paused_until = paused_until.as_deref().map(parse_date).transpose()?;
+            let last_rated_at: i64 = row.try_get("last_rated_at")?;
+
+            counts.users += 1;
+            if stopped {
+                counts.stopped += 1;
+            } else if paused_until.is_some_and(|paused_until| paused_until > today) {
+                counts.paused += 1;
+            }
+            if last_rated_at > 0 && from_timestamp(last_rated_at)?.with_timezone(&timezone).date_naive() == today {
+                counts.active += 1;
             }
         }
+        Ok(counts)
+    }
+
+    /// Get the chats which receive messages, their reminders aren't stopped
+    pub async fn get_receiving_chats(&self) -> Result<Vec<ChatId>, DbError> {
+        let _timer = DbTimer::start("get_receiving_chats");
+        let rows = sqlx::query("SELECT chat_id FROM User WHERE stopped = 0 ORDER BY id")
+            .fetch_all(&self.pool_)
+            .await?;
+        rows.iter()
+            .map(|row| Ok(ChatId(row.try_get("chat_id")?)))
+            .collect()
+    }
+
+    /// Add a tracker to its user, the first reminder is the next one after the creation
+    ///
+    /// # Return
+    /// Return the id of the tracker, or None if the user already has a tracker with this name
+    pub async fn add_tracker(&self, tracker: &Tracker) -> Result<Option<i64>, DbError> {
+        let _timer = DbTimer::start("add_tracker");
+        let user_id = self.get_user_id_by_chat_id(tracker.get_user().get_chat_id()).await?;
+        let result = sqlx::query("INSERT INTO Tracker (user_id, name, question, scale, hour, minute,
+                                                       nudge_hour, nudge_minute, last_prompt)
+                                  VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                  ON CONFLICT (user_id, name) DO NOTHING")
+            .bind(user_id)
+            .bind(tracker.get_name())
+            .bind(tracker.get_question())
+            .bind(tracker.get_scale().to_string())
+            .bind(tracker.get_hour())
+            .bind(tracker.get_minute())
+            .bind(tracker.get_nudge().map(|n| n.0))
+            .bind(tracker.get_nudge().map(|n| n.1))
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool_)
+            .await?;
+
+        match result.rows_affected() {
+            0 => Ok(None),
+            _ => Ok(Some(result.last_insert_rowid())),
+        }
+    }
 
-        conn.close();
-        user_exist
+    /// Delete a tracker and all its rank days
+    pub async fn delete_tracker(&self, tracker_id: i64) -> Result<(), DbError> {
+        let _timer = DbTimer::start("delete_tracker");
+        let mut tx = self.pool_.begin().await?;
+        sqlx::query("DELETE FROM Group_rank
+                     WHERE rank_day_id IN (SELECT id FROM Rank_day WHERE tracker_id=?)")
+            .bind(tracker_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM Rank_day WHERE tracker_id=?")
+            .bind(tracker_id)
+            .execute(&mut tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM Tracker WHERE id=?")
+            .bind(tracker_id)
+            .execute(&mut tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::TrackerNotFound(tracker_id));
+        }
+        tx.commit().await?;
+        Ok(())
     }
 
-    pub async fn add_rank_day(&self, rank_day: RankDay) {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Add the rank day of a date, only if the date doesn't exist yet for its tracker
+    ///
+    /// An existing rank day is never changed, it can be ranked or commented meanwhile
+    /// (ex: with /rate or /import).
+    ///
+    /// # Return
+    /// Return the id of the rank day added, None if its date already exists
+    pub async fn add_rank_day(&self, rank_day: RankDay) -> Result<Option<i64>, DbError> {
+        let _timer = DbTimer::start("add_rank_day");
+        let user_id = self.get_user_id_by_chat_id(rank_day.get_user().get_chat_id()).await?;
+
+        let row = sqlx::query("INSERT INTO Rank_day (user_id, tracker_id, date, time, id_msg, rank, comment, rated_at)
+                               VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                               ON CONFLICT (tracker_id, date) DO NOTHING
+                               RETURNING id")
+            .bind(user_id)
+            .bind(rank_day.get_tracker().get_id())
+            .bind(rank_day.get_date().format(DATE_FORMAT).to_string())
+            .bind(rank_day.get_time().timestamp())
+            .bind(rank_day.get_id_msg().0)
+            .bind(rank_day.get_rank())
+            .bind(rank_day.get_comment())
+            .bind(rank_day.get_rated_at().map(|time| time.timestamp()))
+            .fetch_optional(&self.pool_)
+            .await?;
+        row.map(|row| row.try_get("id")).transpose().map_err(DbError::from)
+    }
 
-        let stmt = conn
-            .prepare("INSERT INTO Rank_day (user_id, time, id_msg, rank) VALUES (?, ?, ?, ?)")
-            .await
-            .unwrap();
+    /// Set the rank of the date of a rank day, the rank day is added if missing
+    pub async fn update_rank(&self, rank_day: RankDay) -> Result<(), DbError> {
+        let _timer = DbTimer::start("update_rank");
+        self.upsert_rank_day(
+            rank_day,
+            "id_msg=excluded.id_msg,
+             rank=excluded.rank,
+             rated_at=excluded.rated_at",
+        )
+        .await?;
+        Ok(())
+    }
 
-        let user = rank_day.get_user();
-        let user_id = self
-            .get_user_id_by_chat_id(user.get_chat_id())
-            .await
-            .expect("404 User not found");
+    /// Insert a rank day or update the columns given if its date already exists for its tracker
+    ///
+    /// # Return
+    /// Return the id of the rank day
+    async fn upsert_rank_day(&self, rank_day: RankDay, update: &str) -> Result<i64, DbError> {
+        let user_id = self.get_user_id_by_chat_id(rank_day.get_user().get_chat_id()).await?;
 
-        let query = stmt
-            .query()
+        let sql = format!("INSERT INTO Rank_day (user_id, tracker_id, date, time, id_msg, rank, comment, rated_at)
+                                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                                 ON CONFLICT (tracker_id, date) DO UPDATE SET {update}
+                                 RETURNING id");
+        let row = sqlx::query(sql.as_str())
             .bind(user_id)
+            .bind(rank_day.get_tracker().get_id())
+            .bind(rank_day.get_date().format(DATE_FORMAT).to_string())
             .bind(rank_day.get_time().timestamp())
             .bind(rank_day.get_id_msg().0)
-            .bind(rank_day.get_rank());
+            .bind(rank_day.get_rank())
+            .bind(rank_day.get_comment())
+            .bind(rank_day.get_rated_at().map(|time| time.timestamp()))
+            .fetch_one(&self.pool_)
+            .await?;
+        Ok(row.try_get("id")?)
+    }
 
-        query
-            .execute(&mut conn)
-            .await
-            .expect("Error when inserting new rank_day");
+    /// Delete a rank day, used when its message couldn't be sent
+    ///
+    /// The rank day is kept if it was ranked or commented meanwhile.
+    pub async fn delete_rank_day(&self, rank_day_id: i64) -> Result<(), DbError> {
+        let _timer = DbTimer::start("delete_rank_day");
+        sqlx::query("DELETE FROM Rank_day WHERE id=? AND rank IS NULL AND comment IS NULL")
+            .bind(rank_day_id)
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_user_id_by_chat_id(&self, id_chat: ChatId) -> Result<i64, DbError> {
+        let result = sqlx::query("SELECT id FROM User WHERE chat_id = ?")
+            .bind(id_chat.0)
+            .fetch_optional(&self.pool_)
+            .await?;
 
-        conn.close();
+        match result {
+            None => Err(DbError::UserNotFound(id_chat)),
+            Some(row) => Ok(row.try_get("id")?),
+        }
     }
 
-    async fn get_user_id_by_chat_id(&self, id_chat:ChatId) -> Option<i64> {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    pub async fn get_user_by_chat_id(&self, id_chat: ChatId) -> Result<Option<User>, DbError> {
+        let _timer = DbTimer::start("get_user_by_chat_id");
+        let result = sqlx::query("SELECT chat_id, telegram_id, username, display_name, timezone, language, paused_until, stopped,
+                                         send_failure, recap_weekly, recap_monthly
+                                  FROM User WHERE chat_id = ?")
+            .bind(id_chat.0)
+            .fetch_optional(&self.pool_)
+            .await?;
 
-        let stmt = conn
-            .prepare("SELECT id FROM User WHERE chat_id = ?")
-            .await
-            .unwrap();
+        result.map(|row| Self::row_to_user(&row)).transpose()
+    }
 
-        let query = stmt
-            .query()
-            .bind(id_chat.0);
+    fn row_to_user(row: &SqliteRow) -> Result<User, DbError> {
+        let chat_id: i64 = row.try_get("chat_id")?;
+        let telegram_id: Option<i64> = row.try_get("telegram_id")?;
+        let username: Option<String> = row.try_get("username")?;
+        let display_name: String = row.try_get("display_name")?;
+        let timezone: String = row.try_get("timezone")?;
+        let timezone = timezone
+            .parse::<Tz>()
+            .map_err(|_| DbError::InvalidData(format!("timezone {timezone}")))?;
 
-        let result = query.fetch_optional(&mut conn).await.unwrap();
+        let language: String = row.try_get("language")?;
+        let language = Language::from_code(&language).ok_or_else(|| DbError::InvalidData(format!("language {language}")))?;
+        let paused_until: Option<String> = row.try_get("paused_until")?;
+        let stopped: bool = row.try_get("stopped")?;
+        let send_failure: Option<String> = row.try_get("send_failure")?;
+        let recap_weekly: bool = row.try_get("recap_weekly")?;
+        let recap_monthly: bool = row.try_get("recap_monthly")?;
 
-        conn.close();
+        let mut user = User::new(ChatId(chat_id), display_name, timezone);
+        user.set_telegram_id(telegram_id.map(|id| UserId(id as u64)));
+        user.set_username(username);
+        user.set_language(language);
+        user.set_paused_until(paused_until.as_deref().map(parse_date).transpose()?);
+        user.set_stopped(stopped);
+        user.set_send_failure(send_failure);
+        user.set_recap_weekly(recap_weekly);
+        user.set_recap_monthly(recap_monthly);
+        Ok(user)
+    }
 
-        match result {
-            None => { None }
-            Some(row) => {
-                let id: i64 = row.try_get("id").unwrap();
-                Some(id)
-            }
+    /// Read a tracker and its user selected with `TRACKER_COLUMNS`
+    fn row_to_tracker(row: &SqliteRow) -> Result<Tracker, DbError> {
+        let user = Self::row_to_user(row)?;
+        let id: i64 = row.try_get("id")?;
+        let name: String = row.try_get("name")?;
+        let question: String = row.try_get("question")?;
+        let scale: String = row.try_get("scale")?;
+        let scale = Scale::parse(&scale).ok_or_else(|| DbError::InvalidData(format!("scale {scale}")))?;
+        let hour: u8 = row.try_get("hour")?;
+        let minute: u8 = row.try_get("minute")?;
+        let nudge_hour: Option<u8> = row.try_get("nudge_hour")?;
+        let nudge_minute: Option<u8> = row.try_get("nudge_minute")?;
+        let last_prompt: Option<i64> = row.try_get("last_prompt")?;
+        let last_nudge: Option<i64> = row.try_get("last_nudge")?;
+
+        let mut tracker = Tracker::new(user, name, scale, hour);
+        tracker.set_id(id);
+        tracker.set_question(question);
+        tracker.set_minute(minute);
+        tracker.set_nudge(nudge_hour.zip(nudge_minute));
+        tracker.set_last_prompt(last_prompt.map(from_timestamp).transpose()?);
+        tracker.set_last_nudge(last_nudge.map(from_timestamp).transpose()?);
+        Ok(tracker)
+    }
+
+    /// Get the trackers of a user, by order of creation
+    pub async fn get_trackers(&self, id_chat: ChatId) -> Result<Vec<Tracker>, DbError> {
+        let _timer = DbTimer::start("get_trackers");
+        let sql = format!("SELECT {TRACKER_COLUMNS}
+                           FROM Tracker
+                           join User on User.id = Tracker.user_id
+                           WHERE User.chat_id=?
+                           ORDER BY Tracker.id");
+        let rows = sqlx::query(sql.as_str())
+            .bind(id_chat.0)
+            .fetch_all(&self.pool_)
+            .await?;
+
+        rows.iter().map(Self::row_to_tracker).collect()
+    }
+
+    pub async fn get_tracker_by_id(&self, tracker_id: i64) -> Result<Option<Tracker>, DbError> {
+        let _timer = DbTimer::start("get_tracker_by_id");
+        let sql = format!("SELECT {TRACKER_COLUMNS}
+                           FROM Tracker
+                           join User on User.id = Tracker.user_id
+                           WHERE Tracker.id=?");
+        let result = sqlx::query(sql.as_str())
+            .bind(tracker_id)
+            .fetch_optional(&self.pool_)
+            .await?;
+
+        result.map(|row| Self::row_to_tracker(&row)).transpose()
+    }
+
+    /// Get the trackers of every user, for the reminders
+    pub async fn get_all_trackers(&self) -> Result<Vec<Tracker>, DbError> {
+        let _timer = DbTimer::start("get_all_trackers");
+        let sql = format!("SELECT {TRACKER_COLUMNS}
+                           FROM Tracker
+                           join User on User.id = Tracker.user_id");
+        let rows = sqlx::query(sql.as_str())
+            .fetch_all(&self.pool_)
+            .await?;
+
+        rows.iter().map(Self::row_to_tracker).collect()
+    }
+
+    pub async fn update_comment(&self, rank_day_id: i64, comment: Option<String>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("update_comment");
+        sqlx::query("UPDATE Rank_day
+                     SET comment=?
+                     WHERE id=?")
+            .bind(comment)
+            .bind(rank_day_id)
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
+
+    /// Get a rank day by its id, with its tracker
+    pub async fn get_rank_day(&self, rank_day_id: i64) -> Result<Option<RankDay>, DbError> {
+        let _timer = DbTimer::start("get_rank_day");
+        let sql = format!("SELECT {RANK_DAY_COLUMNS}, Rank_day.tracker_id
+                           FROM Rank_day
+                           WHERE Rank_day.id=?");
+        let row = match sqlx::query(sql.as_str())
+            .bind(rank_day_id)
+            .fetch_optional(&self.pool_)
+            .await?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let tracker_id: i64 = row.try_get("tracker_id")?;
+        let tracker = self
+            .get_tracker_by_id(tracker_id)
+            .await?
+            .ok_or(DbError::TrackerNotFound(tracker_id))?;
+        Self::row_to_rank_day(tracker, &row).map(Some)
+    }
+
+    /// Read a rank day selected with `RANK_DAY_COLUMNS`
+    fn row_to_rank_day(tracker: Tracker, row: &SqliteRow) -> Result<RankDay, DbError> {
+        let id: i64 = row.try_get("id")?;
+        let tst: i64 = row.try_get("time")?;
+        let date: String = row.try_get("date")?;
+        let id_msg: i32 = row.try_get("id_msg")?;
+        let rank: Option<u8> = row.try_get("rank")?;
+        let comment: Option<String> = row.try_get("comment")?;
+        let rated_at: Option<i64> = row.try_get("rated_at")?;
+
+        let mut rank_day = RankDay::new(tracker, from_timestamp(tst)?, MessageId(id_msg));
+        rank_day.set_id(id);
+        rank_day.set_date(parse_date(&date)?);
+        rank_day.set_rank(rank);
+        rank_day.set_comment(comment);
+        rank_day.set_rated_at(rated_at.map(from_timestamp).transpose()?);
+        Ok(rank_day)
+    }
+
+    /// Get the ranks of a tracker, sorted by date
+    pub async fn get_ranks(&self, tracker_id: i64) -> Result<Vec<(NaiveDate, u8)>, DbError> {
+        let _timer = DbTimer::start("get_ranks");
+        let first = NaiveDate::from_ymd_opt(1, 1, 1).unwrap_or_default();
+        let last = NaiveDate::from_ymd_opt(9999, 12, 31).unwrap_or_default();
+        self.get_ranks_between(tracker_id, first, last).await
+    }
+
+    /// Get every rank day of a tracker, ranked or not, sorted by date
+    ///
+    /// # Arguments
+    /// * `tracker` - The tracker
+    /// * `year` - Only the rank days of this year if set
+    pub async fn get_rank_days(&self, tracker: &Tracker, year: Option<i32>) -> Result<Vec<RankDay>, DbError> {
+        let _timer = DbTimer::start("get_rank_days");
+        let (from, to) = match year {
+            Some(year) => (format!("{year:04}-01-01"), format!("{year:04}-12-31")),
+            None => ("0000-01-01".to_string(), "9999-12-31".to_string()),
+        };
+        let sql = format!("SELECT {RANK_DAY_COLUMNS}
+                           FROM Rank_day
+                           WHERE Rank_day.tracker_id=? AND Rank_day.date BETWEEN ? AND ?
+                           ORDER BY date");
+        let rows = sqlx::query(sql.as_str())
+            .bind(tracker.get_id())
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool_)
+            .await?;
+
+        rows.iter()
+            .map(|row| Self::row_to_rank_day(tracker.clone(), row))
+            .collect()
+    }
+
+    /// Save imported days of a tracker in a single transaction
+    ///
+    /// The days without rank yet are always filled, the time of an imported day is the time
+    /// of the reminder of the tracker on this date and it has no message. A rank without the time
+    /// it was given is dated by the import.
+    ///
+    /// # Arguments
+    /// * `tracker` - The tracker of the days imported
+    /// * `rows` - The days imported
+    /// * `replace` - If true, the ranks and comments already saved are replaced
+    ///
+    /// # Return
+    /// Return the number of days saved
+    pub async fn import_rank_days(&self, tracker: &Tracker, rows: &[ImportRow], replace: bool) -> Result<u64, DbError> {
+        let _timer = DbTimer::start("import_rank_days");
+        let user = tracker.get_user();
+        let user_id = self.get_user_id_by_chat_id(user.get_chat_id()).await?;
+        let condition = match replace {
+            true => "",
+            false => "WHERE Rank_day.rank IS NULL",
+        };
+        let sql = format!("INSERT INTO Rank_day (user_id, tracker_id, date, time, id_msg, rank, comment, rated_at)
+                                 VALUES (?, ?, ?, ?, 0, ?, ?, ?)
+                                 ON CONFLICT (tracker_id, date) DO UPDATE SET
+                                     rank=excluded.rank,
+                                     comment=COALESCE(excluded.comment, Rank_day.comment),
+                                     rated_at=excluded.rated_at
+                                 {condition}");
+
+        let imported_at = Utc::now().timestamp();
+        let mut tx = self.pool_.begin().await?;
+        let mut saved = 0;
+        for row in rows {
+            let time = schedule::occurrence_on(user.get_timezone(), tracker.get_hour(), tracker.get_minute(), row.date);
+            let result = sqlx::query(sql.as_str())
+                .bind(user_id)
+                .bind(tracker.get_id())
+                .bind(row.date.format(DATE_FORMAT).to_string())
+                .bind(time.timestamp())
+                .bind(row.rank)
+                .bind(row.comment.clone())
+                .bind(row.rated_at.map_or(imported_at, |time| time.timestamp()))
+                .execute(&mut tx)
+                .await?;
+            saved += result.rows_affected();
         }
+        tx.commit().await?;
+        Ok(saved)
     }
 
-    pub async fn get_user_by_chat_id(&self, id_chat: ChatId) -> Option<User> {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Get the ranks of a tracker between two local dates (included), sorted by date
+    pub async fn get_ranks_between(&self, tracker_id: i64, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, u8)>, DbError> {
+        let _timer = DbTimer::start("get_ranks_between");
+        let rows = sqlx::query("SELECT date, rank
+                                FROM Rank_day
+                                WHERE tracker_id=? AND rank IS NOT NULL
+                                AND date BETWEEN ? AND ?
+                                ORDER BY date")
+            .bind(tracker_id)
+            .bind(from.format(DATE_FORMAT).to_string())
+            .bind(to.format(DATE_FORMAT).to_string())
+            .fetch_all(&self.pool_)
+            .await?;
 
-        let stmt = conn
-            .prepare("SELECT chat_id, username, hour FROM User WHERE chat_id = ?")
-            .await
-            .unwrap();
+        let mut vec = Vec::new();
 
-        let query = stmt
-            .query()
-            .bind(id_chat.0);
+        for row in rows {
+            let date: String = row.try_get("date")?;
+            let rank: u8 = row.try_get("rank")?;
+            vec.push((parse_date(&date)?, rank));
+        }
+        Ok(vec)
+    }
 
-        let result = query.fetch_optional(&mut conn).await.unwrap();
+    /// Get the comments of a tracker between two local dates (included), sorted by date
+    pub async fn get_comments_between(&self, tracker_id: i64, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, String)>, DbError> {
+        let _timer = DbTimer::start("get_comments_between");
+        let rows = sqlx::query("SELECT date, comment
+                                FROM Rank_day
+                                WHERE tracker_id=? AND comment IS NOT NULL
+                                AND date BETWEEN ? AND ?
+                                ORDER BY date")
+            .bind(tracker_id)
+            .bind(from.format(DATE_FORMAT).to_string())
+            .bind(to.format(DATE_FORMAT).to_string())
+            .fetch_all(&self.pool_)
+            .await?;
 
-        conn.close();
+        let mut vec = Vec::new();
 
-        match result {
-            None => { None }
-            Some(row) => {
-                let chat_id: i64 = row.try_get("chat_id").unwrap();
-                let username: String = row.try_get("username").unwrap();
-                let hour: u8 = row.try_get("hour").unwrap();
-                let user = User::new(ChatId(chat_id), username, Option::from(hour));
-                Some(user)
-            }
+        for row in rows {
+            let date: String = row.try_get("date")?;
+            let comment: String = row.try_get("comment")?;
+            vec.push((parse_date(&date)?, comment));
         }
+        Ok(vec)
     }
 
-    pub async fn get_time(&self, id_chat: ChatId, id_msg: MessageId) -> Option<DateTime<Utc>> {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Get the first local date ranked for a tracker
+    pub async fn get_first_rank_date(&self, tracker_id: i64) -> Result<Option<NaiveDate>, DbError> {
+        let _timer = DbTimer::start("get_first_rank_date");
+        let row = sqlx::query("SELECT MIN(date) AS date
+                               FROM Rank_day
+                               WHERE tracker_id=? AND rank IS NOT NULL")
+            .bind(tracker_id)
+            .fetch_one(&self.pool_)
+            .await?;
 
-        let stmt = conn
-            .prepare("SELECT time
-                            FROM Rank_day
-                            join User on User.id = Rank_day.user_id
-                            WHERE User.chat_id=? AND Rank_day.id_msg=?")
-            .await
-            .unwrap();
+        let date: Option<String> = row.try_get("date")?;
+        date.as_deref().map(parse_date).transpose()
+    }
 
-        let query = stmt
-            .query()
-            .bind(id_chat.0)
-            .bind(id_msg.0);
+    pub async fn set_time(&self, tracker_id: i64, hour: u8, minute: u8) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_time");
+        let result = sqlx::query("UPDATE Tracker
+                                  SET hour=?, minute=?
+                                  WHERE id=?")
+            .bind(hour)
+            .bind(minute)
+            .bind(tracker_id)
+            .execute(&self.pool_)
+            .await?;
 
-        let result = query.fetch_optional(&mut conn).await.unwrap();
+        match result.rows_affected() {
+            0 => Err(DbError::TrackerNotFound(tracker_id)),
+            _ => Ok(()),
+        }
+    }
 
-        conn.close();
+    pub async fn set_nudge(&self, tracker_id: i64, nudge: Option<(u8, u8)>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_nudge");
+        let result = sqlx::query("UPDATE Tracker
+                                  SET nudge_hour=?, nudge_minute=?
+                                  WHERE id=?")
+            .bind(nudge.map(|n| n.0))
+            .bind(nudge.map(|n| n.1))
+            .bind(tracker_id)
+            .execute(&self.pool_)
+            .await?;
 
-        match result {
-            None => { None }
-            Some(row) => {
-                let tst: i64 = row.try_get("time").unwrap();
-                let time = DateTime::from_timestamp(tst, 0);
-                time
-            }
+        match result.rows_affected() {
+            0 => Err(DbError::TrackerNotFound(tracker_id)),
+            _ => Ok(()),
         }
     }
 
-    pub async fn update_rank(&self, id_chat: ChatId, id_msg: MessageId, rank: Option<u8>) {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Get the last rank day not ranked yet of a tracker since a time
+    ///
+    /// A rank day of a group is ranked as soon as a member ranked it.
+    pub async fn get_unranked_since(&self, tracker: &Tracker, since: DateTime<Utc>) -> Result<Option<RankDay>, DbError> {
+        let _timer = DbTimer::start("get_unranked_since");
+        let sql = format!("SELECT {RANK_DAY_COLUMNS}
+                           FROM Rank_day
+                           WHERE Rank_day.tracker_id=? AND Rank_day.time>=? AND Rank_day.rank IS NULL
+                           AND NOT EXISTS (SELECT 1 FROM Group_rank WHERE Group_rank.rank_day_id=Rank_day.id)
+                           ORDER BY Rank_day.time DESC");
+        let result = sqlx::query(sql.as_str())
+            .bind(tracker.get_id())
+            .bind(since.timestamp())
+            .fetch_optional(&self.pool_)
+            .await?;
 
-        let stmt = conn
-            .prepare("UPDATE Rank_day
-                            SET rank=?
-                            FROM User
-                            WHERE User.id=Rank_day.user_id AND
-                                  User.chat_id=? AND
-                                  Rank_day.id_msg=?")
-            .await
-            .unwrap();
+        result.map(|row| Self::row_to_rank_day(tracker.clone(), &row)).transpose()
+    }
 
-        let query = stmt
-            .query()
-            .bind(rank)
+    /// Set the message of a rank day
+    pub async fn set_id_msg(&self, rank_day_id: i64, id_msg: MessageId) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_id_msg");
+        sqlx::query("UPDATE Rank_day
+                     SET id_msg=?
+                     WHERE id=?")
+            .bind(id_msg.0)
+            .bind(rank_day_id)
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
+
+    /// Set the scale proposed to rank the days of a tracker
+    pub async fn set_scale(&self, tracker_id: i64, scale: &Scale) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_scale");
+        let result = sqlx::query("UPDATE Tracker
+                                  SET scale=?
+                                  WHERE id=?")
+            .bind(scale.to_string())
+            .bind(tracker_id)
+            .execute(&self.pool_)
+            .await?;
+
+        match result.rows_affected() {
+            0 => Err(DbError::TrackerNotFound(tracker_id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the question asked to rank the days of a tracker
+    pub async fn set_question(&self, tracker_id: i64, question: &str) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_question");
+        let result = sqlx::query("UPDATE Tracker
+                                  SET question=?
+                                  WHERE id=?")
+            .bind(question)
+            .bind(tracker_id)
+            .execute(&self.pool_)
+            .await?;
+
+        match result.rows_affected() {
+            0 => Err(DbError::TrackerNotFound(tracker_id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move a user to the new id of his chat (ex: a group which became a supergroup)
+    pub async fn set_chat_id(&self, id_chat: ChatId, new_id_chat: ChatId) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_chat_id");
+        let result = sqlx::query("UPDATE User
+                                  SET chat_id=?
+                                  WHERE User.chat_id=?")
+            .bind(new_id_chat.0)
             .bind(id_chat.0)
-            .bind(id_msg.0);
+            .execute(&self.pool_)
+            .await?;
 
-        query.fetch_optional(&mut conn).await.unwrap();
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
+    }
 
-        conn.close();
+    pub async fn set_timezone(&self, id_chat: ChatId, timezone: Tz) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_timezone");
+        let result = sqlx::query("UPDATE User
+                                  SET timezone=?
+                                  WHERE User.chat_id=?")
+            .bind(timezone.name())
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
 
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
     }
 
-    pub async fn set_hour(&self, id_chat: ChatId, hour: u8) -> Result<(), &str> {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Set the language of the messages sent to a user
+    pub async fn set_language(&self, id_chat: ChatId, language: Language) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_language");
+        let result = sqlx::query("UPDATE User
+                                  SET language=?
+                                  WHERE User.chat_id=?")
+            .bind(language.code())
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
 
-        let stmt = conn
-            .prepare("UPDATE User
-                            SET hour=?
-                            WHERE User.chat_id=?")
-            .await
-            .unwrap();
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
+    }
 
-        let h = hour.clone();
+    /// Pause the reminders of a user until a local date, or resume them if None
+    pub async fn set_paused_until(&self, id_chat: ChatId, paused_until: Option<NaiveDate>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_paused_until");
+        let result = sqlx::query("UPDATE User
+                                  SET paused_until=?
+                                  WHERE User.chat_id=?")
+            .bind(paused_until.map(|date| date.format(DATE_FORMAT).to_string()))
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
 
-        let query = stmt
-            .query()
-            .bind(h)
-            .bind(id_chat.0);
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
+    }
 
-        let result = query
-            .execute(&mut conn)
-            .await;
+    /// Stop or restart the reminders of a user
+    pub async fn set_stopped(&self, id_chat: ChatId, stopped: bool) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_stopped");
+        let result = sqlx::query("UPDATE User
+                                  SET stopped=?
+                                  WHERE User.chat_id=?")
+            .bind(stopped)
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
 
-        conn.close();
-        match result {
-            Ok(_) => { Ok(()) }
-            Err(_) => { Err("Error when updating hour") }
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
         }
+    }
 
+    /// Save the error of a message which can't be sent to a user anymore, or clear it if None
+    ///
+    /// The reminders of the user are stopped with the error, and restarted without.
+    pub async fn set_send_failure(&self, id_chat: ChatId, send_failure: Option<String>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_send_failure");
+        let result = sqlx::query("UPDATE User
+                                  SET send_failure=?, send_failed_at=?, stopped=?
+                                  WHERE User.chat_id=?")
+            .bind(send_failure.as_deref())
+            .bind(send_failure.as_ref().map(|_| Utc::now().timestamp()))
+            .bind(send_failure.is_some())
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
+
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Choose the recaps received by a user
+    pub async fn set_recaps(&self, id_chat: ChatId, weekly: bool, monthly: bool) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_recaps");
+        let result = sqlx::query("UPDATE User
+                                  SET recap_weekly=?, recap_monthly=?
+                                  WHERE User.chat_id=?")
+            .bind(weekly)
+            .bind(monthly)
+            .bind(id_chat.0)
+            .execute(&self.pool_)
+            .await?;
+
+        match result.rows_affected() {
+            0 => Err(DbError::UserNotFound(id_chat)),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn set_last_prompt(&self, tracker_id: i64, time: DateTime<Utc>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_last_prompt");
+        self.set_tracker_time(tracker_id, "last_prompt", time).await
+    }
+
+    pub async fn set_last_nudge(&self, tracker_id: i64, time: DateTime<Utc>) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_last_nudge");
+        self.set_tracker_time(tracker_id, "last_nudge", time).await
+    }
+
+    async fn set_tracker_time(&self, tracker_id: i64, column: &str, time: DateTime<Utc>) -> Result<(), DbError> {
+        let sql = format!("UPDATE Tracker SET {column}=? WHERE id=?");
+        sqlx::query(sql.as_str())
+            .bind(time.timestamp())
+            .bind(tracker_id)
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the time of the last rank day of a tracker
+    pub async fn get_last_rank_time(&self, tracker_id: i64) -> Result<Option<DateTime<Utc>>, DbError> {
+        let _timer = DbTimer::start("get_last_rank_time");
+        let row = sqlx::query("SELECT MAX(time) AS time
+                               FROM Rank_day
+                               WHERE tracker_id=?")
+            .bind(tracker_id)
+            .fetch_one(&self.pool_)
+            .await?;
+
+        let tst: Option<i64> = row.try_get("time")?;
+        tst.map(from_timestamp).transpose()
     }
 
-    pub async fn get_hours(&self) -> Vec<(ChatId, u8)> {
-        let mut conn = SqliteConnection::connect(self.path_.as_str()).await.unwrap();
+    /// Get the rank day of a tracker for a local date
+    pub async fn get_rank_day_on(&self, tracker: &Tracker, date: NaiveDate) -> Result<Option<RankDay>, DbError> {
+        let _timer = DbTimer::start("get_rank_day_on");
+        let sql = format!("SELECT {RANK_DAY_COLUMNS}
+                           FROM Rank_day
+                           WHERE Rank_day.tracker_id=? AND Rank_day.date=?");
+        let result = sqlx::query(sql.as_str())
+            .bind(tracker.get_id())
+            .bind(date.format(DATE_FORMAT).to_string())
+            .fetch_optional(&self.pool_)
+            .await?;
+
+        result.map(|row| Self::row_to_rank_day(tracker.clone(), &row)).transpose()
+    }
 
-        let stmt = conn
-            .prepare("SELECT chat_id, hour
-                            FROM User")
-            .await
-            .expect("Error when preparing query");
+    /// Set the rank given by a member of a group to a rank day of the group, his previous rank is replaced
+    ///
+    /// # Arguments
+    /// * `rank_day_id` - The rank day of the group
+    /// * `member_id` - The Telegram id of the member
+    /// * `member_name` - The name of the member, updated at each rank
+    /// * `rank` - The rank given by the member
+    pub async fn set_group_rank(&self, rank_day_id: i64, member_id: UserId, member_name: &str, rank: u8) -> Result<(), DbError> {
+        let _timer = DbTimer::start("set_group_rank");
+        sqlx::query("INSERT INTO Group_rank (rank_day_id, member_id, member_name, rank, rated_at)
+                     VALUES (?, ?, ?, ?, ?)
+                     ON CONFLICT (rank_day_id, member_id) DO UPDATE SET
+                         member_name=excluded.member_name,
+                         rank=excluded.rank,
+                         rated_at=excluded.rated_at")
+            .bind(rank_day_id)
+            .bind(member_id.0 as i64)
+            .bind(member_name)
+            .bind(rank)
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool_)
+            .await?;
+        Ok(())
+    }
 
-        let query = stmt
-            .query();
+    /// Get the names of the members of a group who ranked a rank day, by order of rank
+    pub async fn get_group_rank_members(&self, rank_day_id: i64) -> Result<Vec<String>, DbError> {
+        let _timer = DbTimer::start("get_group_rank_members");
+        let rows = sqlx::query("SELECT member_name
+                                FROM Group_rank
+                                WHERE rank_day_id=?
+                                ORDER BY rated_at, id")
+            .bind(rank_day_id)
+            .fetch_all(&self.pool_)
+            .await?;
 
-        let rows = query.fetch_all(&mut conn).await.unwrap();
+        rows.iter().map(|row| Ok(row.try_get("member_name")?)).collect()
+    }
 
-        conn.close();
+    /// Get the ranks of the members of a group between two local dates (included), sorted by date
+    ///
+    /// # Return
+    /// Return the id and the name of the member, the date and the rank of each rank
+    pub async fn get_group_ranks_between(&self, tracker_id: i64, from: NaiveDate, to: NaiveDate) -> Result<Vec<(UserId, String, NaiveDate, u8)>, DbError> {
+        let _timer = DbTimer::start("get_group_ranks_between");
+        let rows = sqlx::query("SELECT Group_rank.member_id, Group_rank.member_name, Rank_day.date, Group_rank.rank
+                                FROM Group_rank
+                                join Rank_day on Rank_day.id = Group_rank.rank_day_id
+                                WHERE Rank_day.tracker_id=? AND Rank_day.date BETWEEN ? AND ?
+                                ORDER BY Rank_day.date, Group_rank.rated_at")
+            .bind(tracker_id)
+            .bind(from.format(DATE_FORMAT).to_string())
+            .bind(to.format(DATE_FORMAT).to_string())
+            .fetch_all(&self.pool_)
+            .await?;
 
         let mut vec = Vec::new();
 
         for row in rows {
-            let chat_id: i64 = row.try_get("chat_id").unwrap();
-            let hour: u8 = row.try_get("hour").unwrap();
-            vec.push((ChatId(chat_id), hour));
+            let member_id: i64 = row.try_get("member_id")?;
+            let member_name: String = row.try_get("member_name")?;
+            let date: String = row.try_get("date")?;
+            let rank: u8 = row.try_get("rank")?;
+            vec.push((UserId(member_id as u64), member_name, parse_date(&date)?, rank));
+        }
+        Ok(vec)
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::DEFAULT_TRACKER;
+    use teloxide::types::UserId;
+
+    /// Create a migrated database in a new file
+    async fn database(name: &str) -> Database {
+        let path = std::env::temp_dir().join(format!("picole-{name}-{}.db", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path}{suffix}"));
+        }
+        let database = Database::new(path);
+        database.migrate(false).await.unwrap();
+        database
+    }
+
+    /// Add a chat with its default tracker, a private chat has the Telegram id of its user
+    async fn add_tracker(database: &Database, chat_id: i64) -> Tracker {
+        let mut user = User::new(ChatId(chat_id), format!("chat {chat_id}"), Tz::UTC);
+        if chat_id > 0 {
+            user.set_telegram_id(Some(UserId(chat_id as u64)));
         }
-        vec
+        database.add_user(user.clone()).await.unwrap();
+        let mut tracker = Tracker::new(user, DEFAULT_TRACKER.to_string(), Scale::default(), 22);
+        tracker.set_id(database.add_tracker(&tracker).await.unwrap().unwrap());
+        tracker
+    }
+
+    async fn add_rank_day(database: &Database, tracker: &Tracker, time: &str) -> i64 {
+        let time = DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc);
+        database.add_rank_day(RankDay::new(tracker.clone(), time, MessageId(1))).await.unwrap().unwrap()
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn deleted_user_leaves_no_group_rank() {
+        let database = database("delete-user").await;
+        let alice = add_tracker(&database, 5).await;
+        add_tracker(&database, 6).await;
+        let group = add_tracker(&database, -100).await;
+        let day = add_rank_day(&database, &group, "2024-05-06T20:00:00Z").await;
+        add_rank_day(&database, &alice, "2024-05-06T20:00:00Z").await;
+        database.set_group_rank(day, UserId(5), "alice", 4).await.unwrap();
+        database.set_group_rank(day, UserId(6), "bob", 2).await.unwrap();
+
+        database.delete_user(ChatId(5)).await.unwrap();
+
+        assert_eq!(database.get_group_rank_members(day).await.unwrap(), vec!["bob".to_string()]);
+        let date = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let ranks = database.get_group_ranks_between(group.get_id(), date, date).await.unwrap();
+        assert_eq!(ranks, vec![(UserId(6), "bob".to_string(), date, 2)]);
+        assert!(database.get_user_by_chat_id(ChatId(5)).await.unwrap().is_none());
+        assert!(database.get_user_by_chat_id(ChatId(6)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn deleted_group_keeps_the_ranks_of_its_members_elsewhere() {
+        let database = database("delete-group").await;
+        add_tracker(&database, 5).await;
+        let group = add_tracker(&database, -100).await;
+        let other = add_tracker(&database, -200).await;
+        let day = add_rank_day(&database, &group, "2024-05-06T20:00:00Z").await;
+        let other_day = add_rank_day(&database, &other, "2024-05-06T20:00:00Z").await;
+        database.set_group_rank(day, UserId(5), "alice", 4).await.unwrap();
+        database.set_group_rank(other_day, UserId(5), "alice", 3).await.unwrap();
+
+        database.delete_user(ChatId(-100)).await.unwrap();
+
+        assert!(database.get_group_rank_members(day).await.unwrap().is_empty());
+        assert_eq!(database.get_group_rank_members(other_day).await.unwrap(), vec!["alice".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn migrated_group_keeps_its_trackers() {
+        let database = database("chat-id").await;
+        let group = add_tracker(&database, -100).await;
+        add_rank_day(&database, &group, "2024-05-06T20:00:00Z").await;
+
+        database.set_chat_id(ChatId(-100), ChatId(-1001234)).await.unwrap();
+
+        assert!(database.get_user_by_chat_id(ChatId(-100)).await.unwrap().is_none());
+        let trackers = database.get_trackers(ChatId(-1001234)).await.unwrap();
+        assert_eq!(trackers.len(), 1);
+        assert!(matches!(database.set_chat_id(ChatId(-100), ChatId(-1)).await, Err(DbError::UserNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn imported_rank_is_dated_by_the_file_or_the_import() {
+        let database = database("import").await;
+        let tracker = add_tracker(&database, 5).await;
+        let rated_at = DateTime::parse_from_rfc3339("2024-05-06T21:30:00Z").unwrap().with_timezone(&Utc);
+        let rows = [
+            ImportRow { date: NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(), rank: 3, comment: None, rated_at: Some(rated_at) },
+            ImportRow { date: NaiveDate::from_ymd_opt(2024, 5, 7).unwrap(), rank: 4, comment: None, rated_at: None },
+        ];
+
+        let before = Utc::now().timestamp();
+        assert_eq!(database.import_rank_days(&tracker, &rows, false).await.unwrap(), 2);
+        let after = Utc::now().timestamp();
+
+        let days = database.get_rank_days(&tracker, None).await.unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].get_rated_at(), Some(rated_at));
+        let imported_at = days[1].get_rated_at().unwrap().timestamp();
+        assert!((before..=after).contains(&imported_at));
+    }
+}
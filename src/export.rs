@@ -0,0 +1,185 @@
+use crate::rank_day::{RankDay, DATE_FORMAT};
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+
+/// Columns of the CSV export, in order
+pub const CSV_HEADER: [&str; 4] = ["date", "rank", "comment", "rated_at"];
+
+/// Format of an export
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn parse(text: &str) -> Option<Format> {
+        match text.trim().to_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Extension of the file of the export
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// A rank day as written in an export
+///
+/// The layout is documented in the README and stable: fields are only added at the end.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ExportRow {
+    pub date: String,
+    pub rank: Option<u8>,
+    pub comment: Option<String>,
+    pub rated_at: Option<String>,
+}
+
+impl From<&RankDay> for ExportRow {
+    fn from(rank_day: &RankDay) -> Self {
+        ExportRow {
+            date: rank_day.get_date().format(DATE_FORMAT).to_string(),
+            rank: rank_day.get_rank(),
+            comment: rank_day.get_comment(),
+            rated_at: rank_day
+                .get_rated_at()
+                .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        }
+    }
+}
+
+/// This function write rank days in an export format
+///
+/// # Arguments
+/// * `format` - The format of the export
+/// * `rank_days` - The rank days sorted by date
+///
+/// # Return
+/// Return the content of the file
+pub fn serialize(format: Format, rank_days: &[RankDay]) -> Result<Vec<u8>, serde_json::Error> {
+    let rows: Vec<ExportRow> = rank_days.iter().map(ExportRow::from).collect();
+    match format {
+        Format::Csv => Ok(to_csv(&rows).into_bytes()),
+        Format::Json => serde_json::to_vec_pretty(&rows),
+    }
+}
+
+/// Write the rows as CSV (RFC 4180) with a header line
+fn to_csv(rows: &[ExportRow]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let fields = [
+            row.date.clone(),
+            row.rank.map(|rank| rank.to_string()).unwrap_or_default(),
+            row.comment.clone().unwrap_or_default(),
+            row.rated_at.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quote a CSV field if needed
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import;
+    use crate::scale::Scale;
+    use crate::tracker::{Tracker, DEFAULT_TRACKER};
+    use crate::user::User;
+    use chrono::{DateTime, NaiveDate, Utc};
+    use teloxide::types::{ChatId, MessageId};
+
+    fn time(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc)
+    }
+
+    /// A ranked day with a comment needing quotes, a ranked day without comment and a day not ranked
+    fn rank_days() -> Vec<RankDay> {
+        let user = User::new(ChatId(5), "alice".to_string(), chrono_tz::Europe::Zurich);
+        let tracker = Tracker::new(user, DEFAULT_TRACKER.to_string(), Scale::parse("0-5").unwrap(), 22);
+        let days = [
+            ("2024-03-01T20:00:00Z", Some(4), Some("fine, \"really\"\nthen tired"), Some("2024-03-01T21:04:12Z")),
+            ("2024-03-02T20:00:00Z", Some(0), None, None),
+            ("2024-03-03T20:00:00Z", None, None, None),
+        ];
+        days.iter()
+            .map(|(prompt, rank, comment, rated_at)| {
+                let mut rank_day = RankDay::new(tracker.clone(), time(prompt), MessageId(1));
+                rank_day.set_rank(*rank);
+                rank_day.set_comment(comment.map(String::from));
+                rank_day.set_rated_at(rated_at.map(time));
+                rank_day
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_layout_is_stable() {
+        assert_eq!(CSV_HEADER, ["date", "rank", "comment", "rated_at"]);
+        let csv = String::from_utf8(serialize(Format::Csv, &rank_days()).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "date,rank,comment,rated_at\r\n\
+             2024-03-01,4,\"fine, \"\"really\"\"\nthen tired\",2024-03-01T21:04:12Z\r\n\
+             2024-03-02,0,,\r\n\
+             2024-03-03,,,\r\n"
+        );
+    }
+
+    #[test]
+    fn json_layout_is_stable() {
+        let json: serde_json::Value = serde_json::from_slice(&serialize(Format::Json, &rank_days()).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"date": "2024-03-01", "rank": 4, "comment": "fine, \"really\"\nthen tired", "rated_at": "2024-03-01T21:04:12Z"},
+                {"date": "2024-03-02", "rank": 0, "comment": null, "rated_at": null},
+                {"date": "2024-03-03", "rank": null, "comment": null, "rated_at": null},
+            ])
+        );
+    }
+
+    #[test]
+    fn export_is_imported_back() {
+        let rank_days = rank_days();
+        let scale = Scale::parse("0-5").unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 3).unwrap();
+        // The days not ranked aren't imported
+        let ranked: Vec<import::ImportRow> = rank_days
+            .iter()
+            .filter_map(|rank_day| {
+                Some(import::ImportRow {
+                    date: rank_day.get_date(),
+                    rank: rank_day.get_rank()?,
+                    comment: rank_day.get_comment(),
+                    rated_at: rank_day.get_rated_at(),
+                })
+            })
+            .collect();
+
+        for format in [Format::Csv, Format::Json] {
+            let content = serialize(format, &rank_days).unwrap();
+            let import = import::parse(&content, &scale, today).unwrap();
+            assert!(import.errors.is_empty(), "{format:?}");
+            assert_eq!(import.rows, ranked, "{format:?}");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+mod de;
+mod en;
+mod fr;
+
+/// Language of the messages of a user
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Language {
+    #[default]
+    En,
+    Fr,
+    De,
+}
+
+/// Messages and names of the dates of a language
+struct Catalog {
+    messages: HashMap<&'static str, &'static str>,
+    months: [&'static str; 12],
+    weekdays: [&'static str; 7],
+    short_weekdays: [&'static str; 7],
+}
+
+impl Catalog {
+    fn new(
+        messages: &[(&'static str, &'static str)],
+        months: [&'static str; 12],
+        weekdays: [&'static str; 7],
+        short_weekdays: [&'static str; 7],
+    ) -> Catalog {
+        Catalog {
+            messages: messages.iter().copied().collect(),
+            months,
+            weekdays,
+            short_weekdays,
+        }
+    }
+}
+
+lazy_static! {
+    static ref CATALOGS: HashMap<Language, Catalog> = HashMap::from([
+        (Language::En, Catalog::new(en::MESSAGES, en::MONTHS, en::WEEKDAYS, en::SHORT_WEEKDAYS)),
+        (Language::Fr, Catalog::new(fr::MESSAGES, fr::MONTHS, fr::WEEKDAYS, fr::SHORT_WEEKDAYS)),
+        (Language::De, Catalog::new(de::MESSAGES, de::MONTHS, de::WEEKDAYS, de::SHORT_WEEKDAYS)),
+    ]);
+}
+
+impl Language {
+    /// Every language of the bot, English first
+    pub const ALL: [Language; 3] = [Language::En, Language::Fr, Language::De];
+
+    /// Find the language of a code of Telegram or of /language (ex: fr, de-CH)
+    ///
+    /// # Return
+    /// Return None if the language isn't translated
+    pub fn from_code(code: &str) -> Option<Language> {
+        let code = code.trim().to_lowercase();
+        let code = code.split(['-', '_']).next().unwrap_or_default();
+        Language::ALL.into_iter().find(|language| language.code() == code)
+    }
+
+    /// Code of the language (ISO 639-1), saved in the database
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Fr => "fr",
+            Language::De => "de",
+        }
+    }
+
+    /// Name of the language in the language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::Fr => "Français",
+            Language::De => "Deutsch",
+        }
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        &CATALOGS[self]
+    }
+
+    /// Message of the catalog, the English one if it isn't translated
+    pub fn get(&self, key: &str) -> Option<&'static str> {
+        self.catalog()
+            .messages
+            .get(key)
+            .or_else(|| CATALOGS[&Language::En].messages.get(key))
+            .copied()
+    }
+
+    /// Message of the catalog without argument
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+
+    /// Message of the catalog with its arguments
+    ///
+    /// # Arguments
+    /// * `key` - The key of the message
+    /// * `args` - The value of each `{name}` of the message
+    ///
+    /// # Return
+    /// Return the message, or the key if no catalog has it
+    pub fn format(&self, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+        let Some(message) = self.get(key) else {
+            log::error!("Message {key} not found");
+            return key.to_string();
+        };
+        let mut message = message.to_string();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        message
+    }
+
+    pub fn month(&self, month: u32) -> &'static str {
+        self.catalog().months[(month as usize).clamp(1, 12) - 1]
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        self.catalog().weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Date with its weekday and its year (ex: Monday 6 May 2024)
+    pub fn date(&self, date: NaiveDate) -> String {
+        self.date_with(date, "date-full", self.weekday(date.weekday()))
+    }
+
+    /// Date with its weekday, without the year (ex: Monday 6 May)
+    pub fn day(&self, date: NaiveDate) -> String {
+        self.date_with(date, "date-day", self.weekday(date.weekday()))
+    }
+
+    /// Short date with the abbreviation of its weekday (ex: Mon 6 May)
+    pub fn short_day(&self, date: NaiveDate) -> String {
+        let weekday = self.catalog().short_weekdays[date.weekday().num_days_from_monday() as usize];
+        self.date_with(date, "date-day", weekday)
+    }
+
+    /// Month and year of a date (ex: May 2024)
+    pub fn month_year(&self, date: NaiveDate) -> String {
+        self.date_with(date, "date-month", "")
+    }
+
+    fn date_with(&self, date: NaiveDate, key: &str, weekday: &str) -> String {
+        self.format(
+            key,
+            &[
+                ("weekday", &weekday),
+                ("day", &date.day()),
+                ("month", &self.month(date.month())),
+                ("year", &date.year()),
+            ],
+        )
+    }
+}
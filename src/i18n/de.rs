@@ -0,0 +1,197 @@
+//! German messages
+
+pub const MONTHS: [&str; 12] = [
+    "Januar", "Februar", "März", "April", "Mai", "Juni",
+    "Juli", "August", "September", "Oktober", "November", "Dezember",
+];
+
+pub const WEEKDAYS: [&str; 7] = ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"];
+
+pub const SHORT_WEEKDAYS: [&str; 7] = ["Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa.", "So."];
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Dates
+    ("date-full", "{weekday}, {day}. {month} {year}"),
+    ("date-day", "{weekday}, {day}. {month}"),
+    ("date-month", "{month} {year}"),
+
+    // Trackers
+    ("question-default", "Wie betrunken bist du"),
+    ("question-tracker", "Wie war dein {name}"),
+
+    // Commands
+    ("command-start", "Diesen Bot starten"),
+    ("command-help", "diesen Text anzeigen."),
+    ("command-settime", "die Uhrzeit der Nachricht festlegen, für einen Tracker wenn zuerst genannt (z. B. /settime 22:30 oder /settime sleep 08:00)"),
+    ("command-setnudge", "eine Erinnerung festlegen, falls dein Tag nicht bewertet ist (z. B. /setnudge 08:00 oder /setnudge off)"),
+    ("command-settimezone", "deine Zeitzone festlegen (z. B. /settimezone Europe/Zurich)"),
+    ("command-setscale", "die Bewertungen festlegen: 0-5, 1-10, emoji oder deine Bezeichnungen (z. B. /setscale schlecht,ok,gut)"),
+    ("command-setquestion", "die tägliche Frage festlegen, leer für die Standardfrage (z. B. /setquestion Wie war dein Tag)"),
+    ("command-newtracker", "einen Tracker hinzufügen, mit der Uhrzeit seiner Nachricht (z. B. /newtracker sleep 08:00)"),
+    ("command-trackers", "deine Tracker auflisten"),
+    ("command-deletetracker", "einen Tracker und alle seine Tage löschen (z. B. /deletetracker sleep)"),
+    ("command-rate", "einen vergangenen Tag bewerten, standardmäßig gestern (z. B. /rate 2024-03-01)"),
+    ("command-pixel", "dein Picole Pixel des Jahres erhalten (z. B. /pixel 2023 oder /pixel sleep 2023)"),
+    ("command-stats", "deine Statistiken des Monats, des Jahres oder insgesamt erhalten (z. B. /stats year)"),
+    ("command-groupstats", "die Statistiken der Mitglieder einer Gruppe erhalten (z. B. /groupstats year)"),
+    ("command-export", "alle deine Tage als Datei erhalten (z. B. /export csv 2023 oder /export json)"),
+    ("command-import", "deine Tage aus einer Datei importieren (CSV oder JSON von /export, oder CSV mit Datum,Bewertung)"),
+    ("command-cancel", "den Kommentar abbrechen, den du schreibst"),
+    ("command-recap", "eine Zusammenfassung deiner Woche am Sonntag, deines Monats am 1. erhalten: weekly, monthly, both oder off (z. B. /recap both)"),
+    ("command-pause", "deine Erinnerungen für einige Tage pausieren, standardmäßig 7 (z. B. /pause 14)"),
+    ("command-stop", "deine Erinnerungen stoppen, deine Tage bleiben erhalten (/start um sie neu zu starten)"),
+    ("command-deleteme", "dein Konto und alle deine Tage löschen"),
+    ("command-language", "die Sprache des Bots wählen: en, fr oder de (z. B. /language de)"),
+
+    // General
+    ("error", "Entschuldigung, etwas ist schiefgelaufen. Bitte versuche es später erneut."),
+    ("start-first", "Du musst diesen Bot zuerst mit /start starten"),
+    ("private-only", "Dieser Befehl ist nur in einem privaten Chat mit mir verfügbar"),
+    ("group-only", "Dieser Befehl ist nur in einer Gruppe verfügbar, nutze /stats für deine Statistiken"),
+    ("admin-only", "Nur die Administratoren der Gruppe können ihre Einstellungen ändern"),
+    ("unknown-command", "Befehl nicht gefunden!"),
+    ("help", "Diese Befehle werden unterstützt (die Befehle eines Trackers nehmen zuerst seinen Namen, z. B. /stats sleep year):"),
+
+    // Buttons
+    ("button-edit", "Ändern"),
+    ("button-add-comment", "Kommentar hinzufügen"),
+    ("button-edit-comment", "Kommentar ändern"),
+    ("button-delete-comment", "Kommentar löschen"),
+    ("button-delete", "Löschen"),
+    ("button-cancel", "Abbrechen"),
+    ("button-import", "Importieren"),
+    ("button-import-replace", "Importieren und meine Tage ersetzen"),
+    ("button-import-keep", "Importieren und meine Tage behalten"),
+
+    // Alerts of the buttons
+    ("alert-expired", "Dieser Button ist abgelaufen, nutze die Befehle um fortzufahren (z. B. /rate)"),
+    ("alert-day-not-found", "Dieser Tag existiert nicht mehr"),
+    ("alert-tracker-not-found", "Dieser Tracker existiert nicht mehr"),
+    ("alert-comment-private-only", "Kommentare sind nur in einem privaten Chat mit mir verfügbar"),
+
+    // /start
+    ("welcome", "Willkommen bei Picole Pixel {name}!"),
+    ("welcome-time", "Du erhältst jeden Tag um {time} eine Nachricht, um deinen Tag zu bewerten."),
+    ("welcome-group-time", "Die Gruppe erhält jeden Tag um {time} eine Nachricht, in der jedes Mitglied seinen Tag bewertet."),
+    ("welcome-settime", "Du kannst die Uhrzeit mit dem Befehl /settime {hh:mm} ändern (z. B. /settime 22:30)."),
+    ("welcome-timezone", "Deine Zeitzone ist {timezone}, du kannst sie mit dem Befehl /settimezone ändern (z. B. /settimezone Europe/Zurich)."),
+    ("welcome-newtracker", "Du kannst andere Dinge mit dem Befehl /newtracker verfolgen (z. B. /newtracker sleep 08:00)."),
+    ("welcome-language", "Du kannst die Sprache mit dem Befehl /language ändern."),
+    ("start-again", "Hallo {name}! Du hast diesen Bot schon gestartet"),
+    ("start-restarted", "Deine Erinnerungen beginnen wieder."),
+
+    // Settings
+    ("settime-done", "Du erhältst deine Nachricht zur Bewertung deines Tages jetzt um {time}{tracker}"),
+    ("settime-invalid", "Uhrzeit ungültig (z. B. /settime 22:30)"),
+    ("setnudge-done", "Du wirst um {time} erinnert, falls dein Tag nicht bewertet ist{tracker}"),
+    ("setnudge-off", "Du wirst nicht mehr erinnert{tracker}"),
+    ("setnudge-invalid", "Uhrzeit ungültig (z. B. /setnudge 08:00 oder /setnudge off)"),
+    ("timezone-done", "Deine Zeitzone ist jetzt {timezone}"),
+    ("timezone-not-found", "Zeitzone nicht gefunden (z. B. /settimezone Europe/Zurich)"),
+    ("timezone-choose", "Wähle deine Zeitzone"),
+    ("setscale-done", "Deine Tage werden bewertet mit: {labels}{tracker}"),
+    ("setscale-invalid", "Skala ungültig, nutze 0-5, 1-10, emoji oder 2 bis {max} verschiedene Bezeichnungen durch Kommas getrennt (z. B. /setscale schlecht,ok,gut)"),
+    ("setquestion-done", "Du wirst gefragt: {question} ... ?{tracker}"),
+    ("language-choose", "Wähle deine Sprache"),
+    ("language-done", "Der Bot spricht jetzt Deutsch"),
+    ("language-invalid", "Sprache nicht verfügbar, nutze en, fr oder de (z. B. /language de)"),
+
+    // Trackers
+    ("newtracker-invalid-name", "Name ungültig, nutze ein Wort aus höchstens {max} Buchstaben, Ziffern, - oder _ (z. B. /newtracker sleep 08:00)"),
+    ("newtracker-invalid-time", "Uhrzeit ungültig (z. B. /newtracker sleep 08:00)"),
+    ("newtracker-too-many", "Du kannst nicht mehr als {max} Tracker haben, lösche einen mit /deletetracker"),
+    ("newtracker-done", "Tracker {name} hinzugefügt, du erhältst seine Nachricht jeden Tag um {time}.\nDu kannst seine Frage mit /setquestion {name} und seine Bewertungen mit /setscale {name} ändern."),
+    ("newtracker-exists", "Du hast schon einen Tracker namens {name}"),
+    ("trackers", "Deine Tracker:"),
+    ("trackers-time", "Jeden Tag um {time}"),
+    ("trackers-nudge", "erinnert um {time}"),
+    ("trackers-ranks", "Bewertungen: {labels}"),
+    ("deletetracker-not-found", "Tracker nicht gefunden, sieh deine Tracker mit /trackers"),
+    ("deletetracker-only", "Du kannst deinen einzigen Tracker nicht löschen"),
+    ("deletetracker-confirm", "Den Tracker {name} und seine {days} bewerteten Tage löschen?"),
+    ("deletetracker-done", "Der Tracker {name} ist gelöscht"),
+    ("delete-cancelled", "Löschen abgebrochen"),
+
+    // Reminders
+    ("recap-invalid", "Zusammenfassung ungültig (z. B. /recap weekly, /recap monthly, /recap both oder /recap off)"),
+    ("recap-both", "Du erhältst mit deiner Erinnerung eine Zusammenfassung deiner Woche am Sonntag und deines Monats am 1."),
+    ("recap-weekly", "Du erhältst mit deiner Erinnerung eine Zusammenfassung deiner Woche am Sonntag"),
+    ("recap-monthly", "Du erhältst mit deiner Erinnerung eine Zusammenfassung deines Monats am 1."),
+    ("recap-off", "Du erhältst keine Zusammenfassung (z. B. /recap both um sie zu erhalten)"),
+    ("recap-week", "Deine Woche von {from} bis {to}"),
+    ("recap-month", "Dein Monat {month}"),
+    ("recap-previous-week", "der Vorwoche"),
+    ("recap-comments", "Kommentare:"),
+    ("pause-invalid", "Die Pause ist eine Anzahl Tage zwischen 1 und {max} (z. B. /pause 14)"),
+    ("pause-done", "Deine Erinnerungen sind pausiert, sie beginnen wieder am {date}. Sende /start um sie vorher neu zu starten."),
+    ("stop-done", "Deine Erinnerungen sind gestoppt, deine Tage bleiben erhalten. Sende /start um sie neu zu starten."),
+    ("deleteme-confirm", "Dein Konto, alle deine Tracker und alle deine Tage löschen? Das kann nicht rückgängig gemacht werden."),
+    ("deleteme-done", "Dein Konto und alle deine Tage sind gelöscht. Auf Wiedersehen!"),
+
+    // Days
+    ("ranked-by", "Bewertet von {members}"),
+    ("day-ranked", "{date} hast du eine {rank} im Picole Pixel vergeben{tracker}"),
+    ("day-comment", "Kommentar: {comment}"),
+    ("rank-not-in-scale", "Diese Bewertung ist nicht in deiner Skala, wähle erneut"),
+    ("rate-future", "Du kannst keinen Tag in der Zukunft bewerten"),
+    ("rate-invalid", "Datum ungültig (z. B. /rate 2024-03-01)"),
+    ("rate-already", "Du hast am {date} schon eine {rank} vergeben{tracker}, nutze den Button Ändern dieses Tages um sie zu ändern"),
+    ("rate-picker", "Diese Tage sind noch nicht bewertet{tracker}:"),
+    ("comment-send", "Sende mir deinen Kommentar für diesen Tag"),
+    ("comment-actual", "Aktueller Kommentar: {comment}"),
+    ("comment-cancel", "Du kannst mit /cancel abbrechen"),
+    ("comment-saved", "Kommentar gespeichert"),
+    ("comment-deleted", "Kommentar gelöscht"),
+    ("cancel-comment", "Kommentar abgebrochen"),
+    ("cancel-import", "Import abgebrochen"),
+    ("cancel-nothing", "Nichts abzubrechen"),
+
+    // Pixels and statistics
+    ("pixel-invalid-year", "Jahr ungültig (z. B. /pixel 2023)"),
+    ("pixel-caption", "Picole Pixel {year} von {name}{tracker}"),
+    ("group-pixel-none", "Kein Mitglied hat {year} einen Tag bewertet{tracker}"),
+    ("group-pixel-caption", "Picole Pixel {year} von {name}{tracker}, {members} Mitglieder"),
+    ("period-month", "für {month}"),
+    ("period-year", "für {year}"),
+    ("period-all", "seit {date}"),
+    ("average", "Durchschnitt: {average}"),
+    ("average-none", "Durchschnitt: kein Tag bewertet"),
+    ("compared-to", "({difference} im Vergleich zu {period})"),
+    ("stats-invalid", "Zeitraum ungültig (z. B. /stats month, /stats year oder /stats all)"),
+    ("stats-none", "Du hast noch keinen Tag bewertet{tracker}"),
+    ("stats-title", "Statistiken {period}{tracker}"),
+    ("stats-rated", "Bewertete Tage: {days}"),
+    ("stats-missed", "Verpasste Tage: {days}"),
+    ("stats-streak", "Längste Serie: {days} Tage"),
+    ("stats-best", "Bester Wochentag: {weekday} ({average})"),
+    ("stats-worst", "Schlechtester Wochentag: {weekday} ({average})"),
+    ("stats-distribution", "Verteilung:"),
+    ("group-stats-invalid", "Zeitraum ungültig (z. B. /groupstats month, /groupstats year oder /groupstats all)"),
+    ("group-stats-none", "Noch kein Mitglied hat einen Tag bewertet{tracker}"),
+    ("group-stats-title", "Gruppenstatistiken {period}{tracker}"),
+    ("group-stats-empty", "Kein Mitglied hat einen Tag bewertet"),
+    ("group-stats-average", "Durchschnitt: {average} ({ranks} Bewertungen)"),
+    ("group-stats-members", "{members} Mitglieder:"),
+    ("group-stats-member", "{name}: {average} an {days} Tagen, längste Serie {streak} Tage"),
+
+    // Export and import
+    ("export-invalid", "Argumente ungültig (z. B. /export csv 2023 oder /export json)"),
+    ("export-none", "Kein Tag zu exportieren"),
+    ("export-caption", "{days} Tage exportiert{tracker}"),
+    ("import-send", "Sende mir die zu importierende Datei{tracker}:\n- die CSV- oder JSON-Datei von /export\n- oder eine CSV-Datei mit einem Datum (JJJJ-MM-TT) und einer Bewertung pro Zeile (z. B. 2023-03-01,4)\nDu siehst vor dem Bestätigen, was importiert wird."),
+    ("import-too-big", "Die Datei ist zu groß, um importiert zu werden"),
+    ("import-invalid", "Die Datei kann nicht importiert werden: {error}"),
+    ("import-preview", "{days} bewertete Tage in der Datei gefunden{tracker}:\n- {new} neue Tage\n- {same} Tage schon gleich bewertet\n- {conflicts} Tage schon anders bewertet"),
+    ("import-ignored", "{lines} Zeilen ignoriert:"),
+    ("import-line", "Zeile {line}"),
+    ("import-entry", "Eintrag {entry}"),
+    ("import-error-text", "die Datei ist keine Textdatei"),
+    ("import-error-json", "das JSON ist ungültig (Zeile {line})"),
+    ("import-error-quote", "ein Anführungszeichen ist in Zeile {line} nicht geschlossen"),
+    ("import-error-date", "Datum „{date}“ ungültig (z. B. 2024-03-01)"),
+    ("import-error-future", "das Datum „{date}“ liegt in der Zukunft"),
+    ("import-error-rank", "die Bewertung „{rank}“ ist nicht in deiner Skala"),
+    ("import-error-time", "Zeit „{time}“ ungültig"),
+    ("import-done", "{days} Tage importiert{tracker}"),
+    ("import-over", "Dieser Import ist vorbei, sende die Datei erneut"),
+];
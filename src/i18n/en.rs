@@ -0,0 +1,173 @@
+//! English messages, also used for the messages not translated in the other languages
+
+pub const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+pub const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+pub const SHORT_WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Dates
+    ("date-full", "{weekday} {day} {month} {year}"),
+    ("date-day", "{weekday} {day} {month}"),
+    ("date-month", "{month} {year}"),
+
+    // Trackers
+    ("question-default", "How drunk are you"),
+    ("question-tracker", "How was your {name}"),
+
+    // General
+    ("error", "Sorry, something went wrong. Please try again later."),
+    ("start-first", "You have to /start this bot first"),
+    ("private-only", "This command is only available in a private chat with me"),
+    ("group-only", "This command is only available in a group, use /stats for your statistics"),
+    ("admin-only", "Only the administrators of the group can change its settings"),
+    ("unknown-command", "Command not found !"),
+    ("help", "These commands are supported (the commands of a tracker take its name first, ex: /stats sleep year):"),
+
+    // Buttons
+    ("button-edit", "Edit"),
+    ("button-add-comment", "Add comment"),
+    ("button-edit-comment", "Edit comment"),
+    ("button-delete-comment", "Delete comment"),
+    ("button-delete", "Delete"),
+    ("button-cancel", "Cancel"),
+    ("button-import", "Import"),
+    ("button-import-replace", "Import and replace my days"),
+    ("button-import-keep", "Import and keep my days"),
+
+    // Alerts of the buttons
+    ("alert-expired", "This button has expired, use the commands to continue (ex: /rate)"),
+    ("alert-day-not-found", "This day doesn't exist anymore"),
+    ("alert-tracker-not-found", "This tracker doesn't exist anymore"),
+    ("alert-comment-private-only", "Comments are only available in a private chat with me"),
+
+    // /start
+    ("welcome", "Welcome to Picole Pixel {name} !"),
+    ("welcome-time", "You will receive every day at {time} a message to evaluate your day."),
+    ("welcome-group-time", "The group will receive every day at {time} a message where each member evaluates his day."),
+    ("welcome-settime", "You can change the time with the command /settime {hh:mm} (ex: /settime 22:30)."),
+    ("welcome-timezone", "Your timezone is {timezone}, you can change it with the command /settimezone (ex: /settimezone Europe/Zurich)."),
+    ("welcome-newtracker", "You can track other things with the command /newtracker (ex: /newtracker sleep 08:00)."),
+    ("welcome-language", "You can change the language with the command /language."),
+    ("start-again", "Hi {name} ! You already start this bot"),
+    ("start-restarted", "Your reminders start again."),
+
+    // Settings
+    ("settime-done", "You will receive your message for evaluate your day at {time} now{tracker}"),
+    ("settime-invalid", "Time not valid (ex: /settime 22:30)"),
+    ("setnudge-done", "You will be reminded at {time} if your day isn't ranked{tracker}"),
+    ("setnudge-off", "You will not be reminded anymore{tracker}"),
+    ("setnudge-invalid", "Time not valid (ex: /setnudge 08:00 or /setnudge off)"),
+    ("timezone-done", "Your timezone is {timezone} now"),
+    ("timezone-not-found", "Timezone not found (ex: /settimezone Europe/Zurich)"),
+    ("timezone-choose", "Choose your timezone"),
+    ("setscale-done", "Your days will be ranked with: {labels}{tracker}"),
+    ("setscale-invalid", "Scale not valid, use 0-5, 1-10, emoji or 2 to {max} different labels separated by commas (ex: /setscale bad,ok,good)"),
+    ("setquestion-done", "You will be asked: {question} ... ?{tracker}"),
+    ("language-choose", "Choose your language"),
+    ("language-done", "The bot speaks English now"),
+    ("language-invalid", "Language not available, use en, fr or de (ex: /language fr)"),
+
+    // Trackers
+    ("newtracker-invalid-name", "Name not valid, use a word of at most {max} letters, digits, - or _ (ex: /newtracker sleep 08:00)"),
+    ("newtracker-invalid-time", "Time not valid (ex: /newtracker sleep 08:00)"),
+    ("newtracker-too-many", "You can't have more than {max} trackers, delete one with /deletetracker"),
+    ("newtracker-done", "Tracker {name} added, you will receive its message every day at {time}.\nYou can change its question with /setquestion {name} and its ranks with /setscale {name}."),
+    ("newtracker-exists", "You already have a tracker named {name}"),
+    ("trackers", "Your trackers:"),
+    ("trackers-time", "Every day at {time}"),
+    ("trackers-nudge", "reminded at {time}"),
+    ("trackers-ranks", "Ranks: {labels}"),
+    ("deletetracker-not-found", "Tracker not found, see your trackers with /trackers"),
+    ("deletetracker-only", "You can't delete your only tracker"),
+    ("deletetracker-confirm", "Delete the tracker {name} and its {days} ranked days ?"),
+    ("deletetracker-done", "The tracker {name} is deleted"),
+    ("delete-cancelled", "Deletion cancelled"),
+
+    // Reminders
+    ("recap-invalid", "Recap not valid (ex: /recap weekly, /recap monthly, /recap both or /recap off)"),
+    ("recap-both", "You receive a recap of your week on Sunday and of your month on the 1st, with your reminder"),
+    ("recap-weekly", "You receive a recap of your week on Sunday, with your reminder"),
+    ("recap-monthly", "You receive a recap of your month on the 1st, with your reminder"),
+    ("recap-off", "You receive no recap (ex: /recap both to receive them)"),
+    ("recap-week", "Your week from {from} to {to}"),
+    ("recap-month", "Your month of {month}"),
+    ("recap-previous-week", "the previous week"),
+    ("recap-comments", "Comments:"),
+    ("pause-invalid", "The pause is a number of days between 1 and {max} (ex: /pause 14)"),
+    ("pause-done", "Your reminders are paused, they start again on {date}. Send /start to restart them before."),
+    ("stop-done", "Your reminders are stopped, your days are kept. Send /start to restart them."),
+    ("deleteme-confirm", "Delete your account, all your trackers and all your days ? This can't be undone."),
+    ("deleteme-done", "Your account and all your days are deleted. Goodbye !"),
+
+    // Days
+    ("ranked-by", "Ranked by {members}"),
+    ("day-ranked", "{date} you put a {rank} on the Picole Pixel{tracker}"),
+    ("day-comment", "Comment: {comment}"),
+    ("rank-not-in-scale", "This rank isn't in your scale, choose again"),
+    ("rate-future", "You can't rank a day in the future"),
+    ("rate-invalid", "Date not valid (ex: /rate 2024-03-01)"),
+    ("rate-already", "You already put a {rank} on {date}{tracker}, use the Edit button of this day to change it"),
+    ("rate-picker", "These days aren't ranked yet{tracker}:"),
+    ("comment-send", "Send me your comment for this day"),
+    ("comment-actual", "Actual comment: {comment}"),
+    ("comment-cancel", "You can cancel with /cancel"),
+    ("comment-saved", "Comment saved"),
+    ("comment-deleted", "Comment deleted"),
+    ("cancel-comment", "Comment cancelled"),
+    ("cancel-import", "Import cancelled"),
+    ("cancel-nothing", "Nothing to cancel"),
+
+    // Pixels and statistics
+    ("pixel-invalid-year", "Year not valid (ex: /pixel 2023)"),
+    ("pixel-caption", "Picole Pixel {year} of {name}{tracker}"),
+    ("group-pixel-none", "No member ranked a day in {year}{tracker}"),
+    ("group-pixel-caption", "Picole Pixel {year} of {name}{tracker}, {members} members"),
+    ("period-month", "of {month}"),
+    ("period-year", "of {year}"),
+    ("period-all", "since {date}"),
+    ("average", "Average: {average}"),
+    ("average-none", "Average: no day ranked"),
+    ("compared-to", "({difference} compared to {period})"),
+    ("stats-invalid", "Period not valid (ex: /stats month, /stats year or /stats all)"),
+    ("stats-none", "You haven't ranked any day yet{tracker}"),
+    ("stats-title", "Statistics {period}{tracker}"),
+    ("stats-rated", "Rated days: {days}"),
+    ("stats-missed", "Missed days: {days}"),
+    ("stats-streak", "Longest streak: {days} days"),
+    ("stats-best", "Best weekday: {weekday} ({average})"),
+    ("stats-worst", "Worst weekday: {weekday} ({average})"),
+    ("stats-distribution", "Distribution:"),
+    ("group-stats-invalid", "Period not valid (ex: /groupstats month, /groupstats year or /groupstats all)"),
+    ("group-stats-none", "No member ranked a day yet{tracker}"),
+    ("group-stats-title", "Group statistics {period}{tracker}"),
+    ("group-stats-empty", "No member ranked a day"),
+    ("group-stats-average", "Average: {average} ({ranks} ranks)"),
+    ("group-stats-members", "{members} members:"),
+    ("group-stats-member", "{name}: {average} on {days} days, longest streak {streak} days"),
+
+    // Export and import
+    ("export-invalid", "Arguments not valid (ex: /export csv 2023 or /export json)"),
+    ("export-none", "No day to export"),
+    ("export-caption", "{days} days exported{tracker}"),
+    ("import-send", "Send me the file to import{tracker}:\n- the CSV or JSON file of /export\n- or a CSV file with a date (YYYY-MM-DD) and a rank by line (ex: 2023-03-01,4)\nYou will see what is imported before confirming."),
+    ("import-too-big", "The file is too big to be imported"),
+    ("import-invalid", "The file can't be imported: {error}"),
+    ("import-preview", "{days} ranked days found in the file{tracker}:\n- {new} new days\n- {same} days already ranked the same\n- {conflicts} days already ranked differently"),
+    ("import-ignored", "{lines} lines ignored:"),
+    ("import-line", "line {line}"),
+    ("import-entry", "entry {entry}"),
+    ("import-error-text", "the file isn't a text file"),
+    ("import-error-json", "the JSON isn't valid (line {line})"),
+    ("import-error-quote", "a quote isn't closed at line {line}"),
+    ("import-error-date", "date \"{date}\" not valid (ex: 2024-03-01)"),
+    ("import-error-future", "date \"{date}\" is in the future"),
+    ("import-error-rank", "rank \"{rank}\" not in your scale"),
+    ("import-error-time", "time \"{time}\" not valid"),
+    ("import-done", "{days} days imported{tracker}"),
+    ("import-over", "This import is over, send the file again"),
+];
@@ -0,0 +1,197 @@
+//! French messages
+
+pub const MONTHS: [&str; 12] = [
+    "janvier", "février", "mars", "avril", "mai", "juin",
+    "juillet", "août", "septembre", "octobre", "novembre", "décembre",
+];
+
+pub const WEEKDAYS: [&str; 7] = ["lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche"];
+
+pub const SHORT_WEEKDAYS: [&str; 7] = ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."];
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Dates
+    ("date-full", "{weekday} {day} {month} {year}"),
+    ("date-day", "{weekday} {day} {month}"),
+    ("date-month", "{month} {year}"),
+
+    // Trackers
+    ("question-default", "À quel point es-tu ivre"),
+    ("question-tracker", "Comment était ton {name}"),
+
+    // Commands
+    ("command-start", "Commencer à utiliser ce bot"),
+    ("command-help", "afficher ce texte."),
+    ("command-settime", "choisir l'heure du message, d'un suivi si nommé en premier (ex : /settime 22:30 ou /settime sleep 08:00)"),
+    ("command-setnudge", "choisir l'heure d'un rappel si ta journée n'est pas notée (ex : /setnudge 08:00 ou /setnudge off)"),
+    ("command-settimezone", "choisir ton fuseau horaire (ex : /settimezone Europe/Zurich)"),
+    ("command-setscale", "choisir les notes proposées : 0-5, 1-10, emoji ou tes libellés (ex : /setscale nul,ok,bien)"),
+    ("command-setquestion", "choisir la question posée chaque jour, vide pour celle par défaut (ex : /setquestion Comment était ta journée)"),
+    ("command-newtracker", "ajouter un suivi, avec l'heure de son message (ex : /newtracker sleep 08:00)"),
+    ("command-trackers", "lister tes suivis"),
+    ("command-deletetracker", "supprimer un suivi et toutes ses journées (ex : /deletetracker sleep)"),
+    ("command-rate", "noter un jour passé, hier par défaut (ex : /rate 2024-03-01)"),
+    ("command-pixel", "recevoir ton Picole Pixel de l'année (ex : /pixel 2023 ou /pixel sleep 2023)"),
+    ("command-stats", "recevoir tes statistiques du mois, de l'année ou de toujours (ex : /stats year)"),
+    ("command-groupstats", "recevoir les statistiques des membres d'un groupe (ex : /groupstats year)"),
+    ("command-export", "recevoir toutes tes journées dans un fichier (ex : /export csv 2023 ou /export json)"),
+    ("command-import", "importer tes journées d'un fichier (CSV ou JSON de /export, ou CSV avec date,note)"),
+    ("command-cancel", "annuler le commentaire en cours"),
+    ("command-recap", "recevoir un récapitulatif de ta semaine le dimanche, de ton mois le 1er : weekly, monthly, both ou off (ex : /recap both)"),
+    ("command-pause", "mettre tes rappels en pause quelques jours, 7 par défaut (ex : /pause 14)"),
+    ("command-stop", "arrêter tes rappels, tes journées sont gardées (/start pour les relancer)"),
+    ("command-deleteme", "supprimer ton compte et toutes tes journées"),
+    ("command-language", "choisir la langue du bot : en, fr ou de (ex : /language fr)"),
+
+    // General
+    ("error", "Désolé, quelque chose s'est mal passé. Réessaie plus tard."),
+    ("start-first", "Tu dois d'abord lancer ce bot avec /start"),
+    ("private-only", "Cette commande n'est disponible que dans une conversation privée avec moi"),
+    ("group-only", "Cette commande n'est disponible que dans un groupe, utilise /stats pour tes statistiques"),
+    ("admin-only", "Seuls les administrateurs du groupe peuvent changer ses réglages"),
+    ("unknown-command", "Commande inconnue !"),
+    ("help", "Ces commandes sont disponibles (les commandes d'un suivi prennent d'abord son nom, ex : /stats sleep year) :"),
+
+    // Buttons
+    ("button-edit", "Modifier"),
+    ("button-add-comment", "Ajouter un commentaire"),
+    ("button-edit-comment", "Modifier le commentaire"),
+    ("button-delete-comment", "Supprimer le commentaire"),
+    ("button-delete", "Supprimer"),
+    ("button-cancel", "Annuler"),
+    ("button-import", "Importer"),
+    ("button-import-replace", "Importer et remplacer mes journées"),
+    ("button-import-keep", "Importer et garder mes journées"),
+
+    // Alerts of the buttons
+    ("alert-expired", "Ce bouton a expiré, utilise les commandes pour continuer (ex : /rate)"),
+    ("alert-day-not-found", "Cette journée n'existe plus"),
+    ("alert-tracker-not-found", "Ce suivi n'existe plus"),
+    ("alert-comment-private-only", "Les commentaires ne sont disponibles que dans une conversation privée avec moi"),
+
+    // /start
+    ("welcome", "Bienvenue sur Picole Pixel {name} !"),
+    ("welcome-time", "Tu recevras chaque jour à {time} un message pour évaluer ta journée."),
+    ("welcome-group-time", "Le groupe recevra chaque jour à {time} un message où chaque membre évalue sa journée."),
+    ("welcome-settime", "Tu peux changer l'heure avec la commande /settime {hh:mm} (ex : /settime 22:30)."),
+    ("welcome-timezone", "Ton fuseau horaire est {timezone}, tu peux le changer avec la commande /settimezone (ex : /settimezone Europe/Zurich)."),
+    ("welcome-newtracker", "Tu peux suivre d'autres choses avec la commande /newtracker (ex : /newtracker sleep 08:00)."),
+    ("welcome-language", "Tu peux changer la langue avec la commande /language."),
+    ("start-again", "Salut {name} ! Tu as déjà lancé ce bot"),
+    ("start-restarted", "Tes rappels reprennent."),
+
+    // Settings
+    ("settime-done", "Tu recevras maintenant ton message pour évaluer ta journée à {time}{tracker}"),
+    ("settime-invalid", "Heure invalide (ex : /settime 22:30)"),
+    ("setnudge-done", "Tu seras relancé à {time} si ta journée n'est pas notée{tracker}"),
+    ("setnudge-off", "Tu ne seras plus relancé{tracker}"),
+    ("setnudge-invalid", "Heure invalide (ex : /setnudge 08:00 ou /setnudge off)"),
+    ("timezone-done", "Ton fuseau horaire est maintenant {timezone}"),
+    ("timezone-not-found", "Fuseau horaire introuvable (ex : /settimezone Europe/Zurich)"),
+    ("timezone-choose", "Choisis ton fuseau horaire"),
+    ("setscale-done", "Tes journées seront notées avec : {labels}{tracker}"),
+    ("setscale-invalid", "Échelle invalide, utilise 0-5, 1-10, emoji ou 2 à {max} libellés différents séparés par des virgules (ex : /setscale nul,ok,bien)"),
+    ("setquestion-done", "On te demandera : {question} ... ?{tracker}"),
+    ("language-choose", "Choisis ta langue"),
+    ("language-done", "Le bot parle français maintenant"),
+    ("language-invalid", "Langue non disponible, utilise en, fr ou de (ex : /language fr)"),
+
+    // Trackers
+    ("newtracker-invalid-name", "Nom invalide, utilise un mot d'au plus {max} lettres, chiffres, - ou _ (ex : /newtracker sleep 08:00)"),
+    ("newtracker-invalid-time", "Heure invalide (ex : /newtracker sleep 08:00)"),
+    ("newtracker-too-many", "Tu ne peux pas avoir plus de {max} suivis, supprimes-en un avec /deletetracker"),
+    ("newtracker-done", "Suivi {name} ajouté, tu recevras son message chaque jour à {time}.\nTu peux changer sa question avec /setquestion {name} et ses notes avec /setscale {name}."),
+    ("newtracker-exists", "Tu as déjà un suivi nommé {name}"),
+    ("trackers", "Tes suivis :"),
+    ("trackers-time", "Chaque jour à {time}"),
+    ("trackers-nudge", "relancé à {time}"),
+    ("trackers-ranks", "Notes : {labels}"),
+    ("deletetracker-not-found", "Suivi introuvable, vois tes suivis avec /trackers"),
+    ("deletetracker-only", "Tu ne peux pas supprimer ton seul suivi"),
+    ("deletetracker-confirm", "Supprimer le suivi {name} et ses {days} journées notées ?"),
+    ("deletetracker-done", "Le suivi {name} est supprimé"),
+    ("delete-cancelled", "Suppression annulée"),
+
+    // Reminders
+    ("recap-invalid", "Récapitulatif invalide (ex : /recap weekly, /recap monthly, /recap both ou /recap off)"),
+    ("recap-both", "Tu reçois un récapitulatif de ta semaine le dimanche et de ton mois le 1er, avec ton rappel"),
+    ("recap-weekly", "Tu reçois un récapitulatif de ta semaine le dimanche, avec ton rappel"),
+    ("recap-monthly", "Tu reçois un récapitulatif de ton mois le 1er, avec ton rappel"),
+    ("recap-off", "Tu ne reçois aucun récapitulatif (ex : /recap both pour les recevoir)"),
+    ("recap-week", "Ta semaine du {from} au {to}"),
+    ("recap-month", "Ton mois de {month}"),
+    ("recap-previous-week", "la semaine précédente"),
+    ("recap-comments", "Commentaires :"),
+    ("pause-invalid", "La pause est un nombre de jours entre 1 et {max} (ex : /pause 14)"),
+    ("pause-done", "Tes rappels sont en pause, ils reprennent le {date}. Envoie /start pour les relancer avant."),
+    ("stop-done", "Tes rappels sont arrêtés, tes journées sont gardées. Envoie /start pour les relancer."),
+    ("deleteme-confirm", "Supprimer ton compte, tous tes suivis et toutes tes journées ? C'est définitif."),
+    ("deleteme-done", "Ton compte et toutes tes journées sont supprimés. Au revoir !"),
+
+    // Days
+    ("ranked-by", "Noté par {members}"),
+    ("day-ranked", "{date} tu as mis un {rank} sur le Picole Pixel{tracker}"),
+    ("day-comment", "Commentaire : {comment}"),
+    ("rank-not-in-scale", "Cette note n'est pas dans ton échelle, choisis à nouveau"),
+    ("rate-future", "Tu ne peux pas noter un jour dans le futur"),
+    ("rate-invalid", "Date invalide (ex : /rate 2024-03-01)"),
+    ("rate-already", "Tu as déjà mis un {rank} le {date}{tracker}, utilise le bouton Modifier de ce jour pour le changer"),
+    ("rate-picker", "Ces jours ne sont pas encore notés{tracker} :"),
+    ("comment-send", "Envoie-moi ton commentaire pour cette journée"),
+    ("comment-actual", "Commentaire actuel : {comment}"),
+    ("comment-cancel", "Tu peux annuler avec /cancel"),
+    ("comment-saved", "Commentaire enregistré"),
+    ("comment-deleted", "Commentaire supprimé"),
+    ("cancel-comment", "Commentaire annulé"),
+    ("cancel-import", "Import annulé"),
+    ("cancel-nothing", "Rien à annuler"),
+
+    // Pixels and statistics
+    ("pixel-invalid-year", "Année invalide (ex : /pixel 2023)"),
+    ("pixel-caption", "Picole Pixel {year} de {name}{tracker}"),
+    ("group-pixel-none", "Aucun membre n'a noté de journée en {year}{tracker}"),
+    ("group-pixel-caption", "Picole Pixel {year} de {name}{tracker}, {members} membres"),
+    ("period-month", "de {month}"),
+    ("period-year", "de {year}"),
+    ("period-all", "depuis le {date}"),
+    ("average", "Moyenne : {average}"),
+    ("average-none", "Moyenne : aucune journée notée"),
+    ("compared-to", "({difference} par rapport à {period})"),
+    ("stats-invalid", "Période invalide (ex : /stats month, /stats year ou /stats all)"),
+    ("stats-none", "Tu n'as encore noté aucune journée{tracker}"),
+    ("stats-title", "Statistiques {period}{tracker}"),
+    ("stats-rated", "Journées notées : {days}"),
+    ("stats-missed", "Journées manquées : {days}"),
+    ("stats-streak", "Plus longue série : {days} jours"),
+    ("stats-best", "Meilleur jour : {weekday} ({average})"),
+    ("stats-worst", "Pire jour : {weekday} ({average})"),
+    ("stats-distribution", "Répartition :"),
+    ("group-stats-invalid", "Période invalide (ex : /groupstats month, /groupstats year ou /groupstats all)"),
+    ("group-stats-none", "Aucun membre n'a encore noté de journée{tracker}"),
+    ("group-stats-title", "Statistiques du groupe {period}{tracker}"),
+    ("group-stats-empty", "Aucun membre n'a noté de journée"),
+    ("group-stats-average", "Moyenne : {average} ({ranks} notes)"),
+    ("group-stats-members", "{members} membres :"),
+    ("group-stats-member", "{name} : {average} sur {days} jours, plus longue série {streak} jours"),
+
+    // Export and import
+    ("export-invalid", "Arguments invalides (ex : /export csv 2023 ou /export json)"),
+    ("export-none", "Aucune journée à exporter"),
+    ("export-caption", "{days} journées exportées{tracker}"),
+    ("import-send", "Envoie-moi le fichier à importer{tracker} :\n- le fichier CSV ou JSON de /export\n- ou un fichier CSV avec une date (AAAA-MM-JJ) et une note par ligne (ex : 2023-03-01,4)\nTu verras ce qui est importé avant de confirmer."),
+    ("import-too-big", "Le fichier est trop gros pour être importé"),
+    ("import-invalid", "Le fichier ne peut pas être importé : {error}"),
+    ("import-preview", "{days} journées notées trouvées dans le fichier{tracker} :\n- {new} nouvelles journées\n- {same} journées déjà notées pareil\n- {conflicts} journées déjà notées différemment"),
+    ("import-ignored", "{lines} lignes ignorées :"),
+    ("import-line", "ligne {line}"),
+    ("import-entry", "entrée {entry}"),
+    ("import-error-text", "le fichier n'est pas un fichier texte"),
+    ("import-error-json", "le JSON n'est pas valide (ligne {line})"),
+    ("import-error-quote", "un guillemet n'est pas fermé à la ligne {line}"),
+    ("import-error-date", "date « {date} » invalide (ex : 2024-03-01)"),
+    ("import-error-future", "la date « {date} » est dans le futur"),
+    ("import-error-rank", "la note « {rank} » n'est pas dans ton échelle"),
+    ("import-error-time", "heure « {time} » invalide"),
+    ("import-done", "{days} journées importées{tracker}"),
+    ("import-over", "Cet import est terminé, envoie à nouveau le fichier"),
+];
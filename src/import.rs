@@ -0,0 +1,345 @@
+use crate::export::{ExportRow, CSV_HEADER};
+use crate::i18n::Language;
+use crate::rank_day::DATE_FORMAT;
+use crate::scale::Scale;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// A ranked day read from an imported file
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImportRow {
+    pub date: NaiveDate,
+    pub rank: u8,
+    pub comment: Option<String>,
+    pub rated_at: Option<DateTime<Utc>>,
+}
+
+/// Errors of an imported file, written in the language of the user
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The file isn't UTF-8
+    NotText,
+    /// The JSON can't be read, with the line of the error
+    InvalidJson(usize),
+    /// A quoted field of the CSV isn't closed, with the line it starts
+    QuoteNotClosed(usize),
+    InvalidDate(String),
+    /// The day is after today in the timezone of the user
+    FutureDate(String),
+    /// The rank isn't a rank or a label of the scale
+    InvalidRank(String),
+    InvalidTime(String),
+}
+
+impl ImportError {
+    /// Message of the error in a language
+    pub fn text(&self, language: Language) -> String {
+        match self {
+            ImportError::NotText => language.text("import-error-text"),
+            ImportError::InvalidJson(line) => language.format("import-error-json", &[("line", line)]),
+            ImportError::QuoteNotClosed(line) => language.format("import-error-quote", &[("line", line)]),
+            ImportError::InvalidDate(date) => language.format("import-error-date", &[("date", date)]),
+            ImportError::FutureDate(date) => language.format("import-error-future", &[("date", date)]),
+            ImportError::InvalidRank(rank) => language.format("import-error-rank", &[("rank", rank)]),
+            ImportError::InvalidTime(time) => language.format("import-error-time", &[("time", time)]),
+        }
+    }
+}
+
+/// Position of a row in an imported file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    /// Line of a CSV, from 1
+    Line(usize),
+    /// Entry of a JSON array, from 1
+    Entry(usize),
+}
+
+impl Position {
+    /// Name of the position in a language (ex: line 3)
+    pub fn text(&self, language: Language) -> String {
+        match self {
+            Position::Line(line) => language.format("import-line", &[("line", line)]),
+            Position::Entry(entry) => language.format("import-entry", &[("entry", entry)]),
+        }
+    }
+}
+
+/// A line of an imported file, not checked yet
+struct RawRow {
+    date: String,
+    rank: Option<String>,
+    comment: Option<String>,
+    rated_at: Option<String>,
+}
+
+impl From<ExportRow> for RawRow {
+    fn from(row: ExportRow) -> Self {
+        RawRow {
+            date: row.date,
+            rank: row.rank.map(|rank| rank.to_string()),
+            comment: row.comment,
+            rated_at: row.rated_at,
+        }
+    }
+}
+
+/// Result of the reading of an imported file
+pub struct Import {
+    /// The ranked days, sorted by date, one by date
+    pub rows: Vec<ImportRow>,
+    /// The rows which can't be imported, with the reason
+    pub errors: Vec<(Position, ImportError)>,
+}
+
+/// This function read a file to import
+///
+/// The accepted files are the CSV and JSON exports of the bot, and a CSV with a date and a
+/// rank by line (header optional). The days without rank are ignored, the last line wins
+/// when a date is repeated.
+///
+/// # Arguments
+/// * `content` - The content of the file
+/// * `scale` - The scale of the user, a rank is one of its labels or ranks
+/// * `today` - The date of today for the user, the days after it are refused
+///
+/// # Return
+/// Return the days read or an error if the file can't be read at all
+pub fn parse(content: &[u8], scale: &Scale, today: NaiveDate) -> Result<Import, ImportError> {
+    let text = std::str::from_utf8(content).map_err(|_| ImportError::NotText)?;
+    let text = text.trim_start_matches('\u{feff}');
+
+    let lines = match text.trim_start().starts_with('[') {
+        true => parse_json(text)?,
+        false => parse_csv(text)?,
+    };
+
+    let mut rows: HashMap<NaiveDate, ImportRow> = HashMap::new();
+    let mut errors = Vec::new();
+    for (position, row) in lines {
+        match to_import_row(&row, scale, today) {
+            Ok(Some(row)) => {
+                rows.insert(row.date, row);
+            }
+            Ok(None) => {}
+            Err(e) => errors.push((position, e)),
+        }
+    }
+
+    let mut rows: Vec<ImportRow> = rows.into_values().collect();
+    rows.sort_by_key(|row| row.date);
+    Ok(Import { rows, errors })
+}
+
+/// Check a row read, None if the day isn't ranked
+fn to_import_row(row: &RawRow, scale: &Scale, today: NaiveDate) -> Result<Option<ImportRow>, ImportError> {
+    let date = NaiveDate::parse_from_str(row.date.trim(), DATE_FORMAT)
+        .map_err(|_| ImportError::InvalidDate(row.date.clone()))?;
+    if date > today {
+        return Err(ImportError::FutureDate(row.date.clone()));
+    }
+
+    let rank = match row.rank.as_deref().map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(rank) => rank,
+    };
+    let rank = scale
+        .parse_rank(rank)
+        .ok_or_else(|| ImportError::InvalidRank(rank.to_string()))?;
+
+    let rated_at = match row.rated_at.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(rated_at) => Some(
+            DateTime::parse_from_rfc3339(rated_at)
+                .map_err(|_| ImportError::InvalidTime(rated_at.to_string()))?
+                .with_timezone(&Utc),
+        ),
+    };
+
+    Ok(Some(ImportRow {
+        date,
+        rank,
+        comment: row.comment.clone().filter(|comment| !comment.is_empty()),
+        rated_at,
+    }))
+}
+
+/// Read the JSON export, with the position of each row
+fn parse_json(text: &str) -> Result<Vec<(Position, RawRow)>, ImportError> {
+    let rows: Vec<ExportRow> = serde_json::from_str(text).map_err(|e| ImportError::InvalidJson(e.line()))?;
+    Ok(rows.into_iter().enumerate().map(|(i, row)| (Position::Entry(i + 1), RawRow::from(row))).collect())
+}
+
+/// Read the CSV export or a CSV of dates and ranks, with the position of each row
+fn parse_csv(text: &str) -> Result<Vec<(Position, RawRow)>, ImportError> {
+    let records = csv_records(text)?;
+    let mut records = records.into_iter().peekable();
+
+    // Columns given by the header, or date and rank without header
+    let mut columns: HashMap<String, usize> = HashMap::from([
+        (CSV_HEADER[0].to_string(), 0),
+        (CSV_HEADER[1].to_string(), 1),
+    ]);
+    if let Some((_, header)) = records.peek() {
+        let names: Vec<String> = header.iter().map(|name| name.trim().to_lowercase()).collect();
+        if let Some(date) = names.iter().position(|name| name == CSV_HEADER[0]) {
+            columns = names.into_iter().enumerate().map(|(i, name)| (name, i)).collect();
+            // Other apps name the rank differently (ex: mood), it's the column after the date
+            columns.entry(CSV_HEADER[1].to_string()).or_insert(date + 1);
+            records.next();
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line, record) in records {
+        let field = |name: &str| {
+            columns
+                .get(name)
+                .and_then(|i| record.get(*i))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        rows.push((
+            Position::Line(line),
+            RawRow {
+                date: field(CSV_HEADER[0]).unwrap_or_default(),
+                rank: field(CSV_HEADER[1]),
+                comment: field(CSV_HEADER[2]),
+                rated_at: field(CSV_HEADER[3]),
+            },
+        ));
+    }
+    Ok(rows)
+}
+
+/// Split a CSV (RFC 4180) in records with the number of their first line, empty lines are skipped
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, ImportError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ImportError::QuoteNotClosed(start));
+    }
+    record.push(field);
+    if record.iter().any(|field| !field.trim().is_empty()) {
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn record(line: usize, fields: &[&str]) -> (usize, Vec<String>) {
+        (line, fields.iter().map(|field| field.to_string()).collect())
+    }
+
+    #[test]
+    fn records_are_split() {
+        let records = csv_records("date,rank\n2024-03-01,3\n\n2024-03-02,4").unwrap();
+        assert_eq!(records, vec![record(1, &["date", "rank"]), record(2, &["2024-03-01", "3"]), record(4, &["2024-03-02", "4"])]);
+    }
+
+    #[test]
+    fn quoted_fields_are_read() {
+        let records = csv_records("2024-03-01,3,\"fine, then tired\"\n\"2024-03-02\",\"4\",\"\"").unwrap();
+        assert_eq!(records, vec![record(1, &["2024-03-01", "3", "fine, then tired"]), record(2, &["2024-03-02", "4", ""])]);
+    }
+
+    #[test]
+    fn doubled_quotes_are_a_quote() {
+        let records = csv_records("2024-03-01,3,\"a \"\"good\"\" day\"").unwrap();
+        assert_eq!(records, vec![record(1, &["2024-03-01", "3", "a \"good\" day"])]);
+    }
+
+    #[test]
+    fn crlf_lines_are_read() {
+        let records = csv_records("date,rank\r\n2024-03-01,3\r\n").unwrap();
+        assert_eq!(records, vec![record(1, &["date", "rank"]), record(2, &["2024-03-01", "3"])]);
+    }
+
+    #[test]
+    fn line_break_in_quotes_is_kept() {
+        let records = csv_records("2024-03-01,3,\"first line\r\nsecond line\"\n2024-03-02,4").unwrap();
+        assert_eq!(
+            records,
+            vec![record(1, &["2024-03-01", "3", "first line\r\nsecond line"]), record(3, &["2024-03-02", "4"])]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_refused() {
+        assert_eq!(csv_records("2024-03-01,3\n2024-03-02,4,\"not closed\n"), Err(ImportError::QuoteNotClosed(2)));
+    }
+
+    #[test]
+    fn future_days_are_refused() {
+        let scale = Scale::parse("0-5").unwrap();
+        let content = "date,rank\n2024-03-01,3\n2024-03-02,4\n2024-03-03,5";
+        let import = parse(content.as_bytes(), &scale, date(2024, 3, 2)).unwrap();
+        let dates: Vec<NaiveDate> = import.rows.iter().map(|row| row.date).collect();
+        assert_eq!(dates, vec![date(2024, 3, 1), date(2024, 3, 2)]);
+        assert_eq!(import.errors, vec![(Position::Line(4), ImportError::FutureDate("2024-03-03".to_string()))]);
+    }
+
+    #[test]
+    fn errors_are_translated() {
+        let errors = [
+            ImportError::NotText,
+            ImportError::InvalidJson(2),
+            ImportError::QuoteNotClosed(3),
+            ImportError::InvalidDate("03/01/2024".to_string()),
+            ImportError::FutureDate("2099-01-01".to_string()),
+            ImportError::InvalidRank("7".to_string()),
+            ImportError::InvalidTime("noon".to_string()),
+        ];
+        assert_eq!(ImportError::FutureDate("2099-01-01".to_string()).text(Language::En), "date \"2099-01-01\" is in the future");
+        assert_eq!(Position::Line(4).text(Language::Fr), "ligne 4");
+        for error in &errors {
+            let english = error.text(Language::En);
+            assert!(!english.starts_with("import-") && !english.contains('{'), "{english}");
+            // Each language has its own message, not the English one
+            for language in [Language::Fr, Language::De] {
+                assert_ne!(error.text(language), english);
+            }
+        }
+    }
+}
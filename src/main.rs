@@ -7,117 +7,615 @@ use crate::rank_day::RankDay;
 mod db;
 use db::*;
 
+mod migration;
+
+mod pixel;
+
+mod scale;
+use scale::Scale;
+
+mod schedule;
+
+mod stats;
+use stats::{Period, Stats};
+
+mod export;
+
+mod import;
+use import::ImportRow;
+
+mod tracker;
+use tracker::{Tracker, DEFAULT_TRACKER};
+
+mod callback;
+use callback::{CallbackAction, ImportChoice};
+
+mod send;
+
+mod recap;
+use recap::Recap;
+
+mod i18n;
+use i18n::Language;
+
+mod webhook;
+
+mod config;
+use config::CONFIG;
+
+mod metrics;
+use metrics::METRICS;
+
+mod monitoring;
+
+use std::collections::HashMap;
 use std::env;
-use async_std::task;
-use chrono::{DateTime, Datelike, Utc, Timelike, Local};
+use std::path::Path;
+use std::sync::Mutex;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
 use lazy_static::lazy_static;
 use std::convert::From;
 use std::error::Error;
-use teloxide::{payloads::SendMessageSetters, prelude::*, types::*, utils::command::BotCommands};
+use teloxide::{net::Download, payloads::SendMessageSetters, prelude::*, types::*, utils::command::BotCommands, ApiError, RequestError};
+use tokio::sync::Notify;
 use tokio::time::Duration;
 
 lazy_static! {
-    static ref LIST_RANK: [&'static str; 6] = ["0", "1", "2", "3", "4", "5"];
+    static ref DATABASE: Database = Database::new(CONFIG.get_path_database());
 }
 
+/// Number of past days proposed by /rate when they aren't ranked
+const RATE_PICKER_DAYS: i64 = 14;
+
+/// Biggest number of trackers of a user
+const MAX_TRACKERS: usize = 10;
+
+/// Number of days paused by /pause without argument, and the biggest number accepted
+const DEFAULT_PAUSE_DAYS: u32 = 7;
+const MAX_PAUSE_DAYS: u32 = 365;
+
 lazy_static! {
-    static ref DATABASE: Database = Database::new(
-        env::var("PATH_DATABASE")
-        .expect("$PATH_DATABASE is not set")
-    );
+    /// Wake up the scheduler when the schedule of a user changed
+    static ref SCHEDULE_CHANGED: Notify = Notify::new();
+}
+
+lazy_static! {
+    /// Rank day id (by chat and by user who used the button) waiting for a comment as his next text message
+    static ref PENDING_COMMENTS: Mutex<HashMap<(ChatId, UserId), i64>> = Mutex::new(HashMap::new());
+}
+
+/// Biggest file accepted by /import
+const MAX_IMPORT_SIZE: u32 = 1024 * 1024;
+
+lazy_static! {
+    /// Tracker (by chat) chosen with /import for the next file sent
+    static ref IMPORT_TRACKERS: Mutex<HashMap<ChatId, i64>> = Mutex::new(HashMap::new());
+
+    /// Tracker and days read from a file (by chat) waiting for the confirmation of the import
+    static ref PENDING_IMPORTS: Mutex<HashMap<ChatId, (i64, Vec<ImportRow>)>> = Mutex::new(HashMap::new());
+}
+
+/// Time waited between the messages of a broadcast, Telegram accepts about 30 messages per second
+const BROADCAST_INTERVAL: Duration = Duration::from_millis(50);
+
+lazy_static! {
+    /// Message (by administrator chat) waiting for the confirmation of its broadcast
+    static ref PENDING_BROADCASTS: Mutex<HashMap<ChatId, String>> = Mutex::new(HashMap::new());
 }
 
 /// These commands are supported:
 #[derive(BotCommands)]
 #[command(
     rename_rule = "lowercase",
-    description = "These commands are supported:"
+    description = "These commands are supported (the commands of a tracker take its name first, ex: /stats sleep year):"
 )]
 enum Command {
     #[command(description = "Start to use this bot")]
     Start,
     #[command(description = "display this text.")]
     Help,
-    #[command(description = "set your hour to receive message (ex: /sethour 22)")]
-    SetHour(u8),
+    #[command(description = "set your time to receive message, for a tracker if named first (ex: /settime 22:30 or /settime sleep 08:00)")]
+    SetTime(String),
+    #[command(description = "set a time to be reminded if your day isn't ranked (ex: /setnudge 08:00 or /setnudge off)")]
+    SetNudge(String),
+    #[command(description = "set your timezone (ex: /settimezone Europe/Zurich)")]
+    SetTimezone(String),
+    #[command(description = "set the ranks proposed: 0-5, 1-10, emoji or your labels (ex: /setscale bad,ok,good)")]
+    SetScale(String),
+    #[command(description = "set the question asked every day, empty for the default (ex: /setquestion How was your day)")]
+    SetQuestion(String),
+    #[command(description = "add a tracker, with its time to receive message (ex: /newtracker sleep 08:00)")]
+    NewTracker(String),
+    #[command(description = "list your trackers")]
+    Trackers,
+    #[command(description = "delete a tracker and all its days (ex: /deletetracker sleep)")]
+    DeleteTracker(String),
+    #[command(description = "rank a past day, yesterday by default (ex: /rate 2024-03-01)")]
+    Rate(String),
+    #[command(description = "get your Picole Pixel of the year (ex: /pixel 2023 or /pixel sleep 2023)")]
+    Pixel(String),
+    #[command(description = "get your statistics of the month, the year or all time (ex: /stats year)")]
+    Stats(String),
+    #[command(description = "get the statistics of the members of a group (ex: /groupstats year)")]
+    GroupStats(String),
+    #[command(description = "get all your days as a file (ex: /export csv 2023 or /export json)")]
+    Export(String),
+    #[command(description = "import your days from a file (CSV or JSON of /export, or CSV with date,rank)")]
+    Import(String),
+    #[command(description = "cancel the comment you are writing")]
+    Cancel,
+    #[command(description = "receive a recap of your week on Sunday, of your month on the 1st: weekly, monthly, both or off (ex: /recap both)")]
+    Recap(String),
+    #[command(description = "pause your reminders for some days, 7 by default (ex: /pause 14)")]
+    Pause(String),
+    #[command(description = "stop your reminders, your days are kept (/start to restart them)")]
+    Stop,
+    #[command(description = "delete your account and all your days")]
+    DeleteMe,
+    #[command(description = "choose the language of the bot: en, fr or de (ex: /language fr)")]
+    Language(String),
+}
+
+impl Command {
+    /// In a group, these commands change the settings shared by the members
+    fn is_setting(&self) -> bool {
+        matches!(
+            self,
+            Command::Start
+                | Command::SetTime(_)
+                | Command::SetNudge(_)
+                | Command::SetTimezone(_)
+                | Command::SetScale(_)
+                | Command::SetQuestion(_)
+                | Command::NewTracker(_)
+                | Command::DeleteTracker(_)
+                | Command::Pause(_)
+                | Command::Stop
+                | Command::DeleteMe
+                | Command::Language(_)
+        )
+    }
+
+    /// These commands are about the days of a single user
+    fn is_private(&self) -> bool {
+        matches!(self, Command::Export(_) | Command::Import(_) | Command::Recap(_))
+    }
+}
+
+/// Commands of the administrators of the bot, the chats listed in `admins` of the configuration:
+#[derive(BotCommands, Clone)]
+#[command(rename_rule = "lowercase", description = "Commands of the administrators of the bot:")]
+enum AdminCommand {
+    #[command(description = "count the users, the users active today and the users paused")]
+    Users,
+    #[command(description = "send a message to every user, after a preview (ex: /broadcast New version !)")]
+    Broadcast(String),
+    #[command(description = "show the settings of a user (ex: /user 1234)")]
+    User(String),
+    #[command(description = "send again today's reminders of a user (ex: /forceprompt 1234)")]
+    ForcePrompt(String),
 }
 
 #[tokio::main]
 async fn main() {
     pretty_env_logger::init();
-    log::info!("Starting command bot...");
 
-    println!("Let's go");
+    // Check the configuration before anything starts
+    lazy_static::initialize(&CONFIG);
+
+    // With --check, only show the migrations to apply without saving them
+    if env::args().any(|arg| arg == "--check") {
+        // A missing database isn't created, all the migrations would be applied to it
+        let pending = match Path::new(&CONFIG.get_path_database()).exists() {
+            true => DATABASE.migrate(true).await.expect("Failed to migrate database"),
+            false => migration::MIGRATIONS.iter().collect(),
+        };
+        if pending.is_empty() {
+            println!("Database is up to date");
+        }
+        for migration in pending {
+            println!("Migration {} {} would be applied", migration.version, migration.name);
+        }
+        return;
+    }
+
+    let token = CONFIG.get_token().expect("The token of the bot ($TELOXIDE_TOKEN) is not set");
+    log::info!(
+        "Starting command bot, new trackers at {:02}:00 {} with {}, {} administrators",
+        CONFIG.get_default_hour(),
+        CONFIG.get_default_timezone(),
+        CONFIG.get_default_scale(),
+        CONFIG.get_admins().len()
+    );
+
+    DATABASE.migrate(false).await.expect("Failed to migrate database");
 
     // Create the bot
-    let bot = Bot::from_env();
+    let bot = Bot::new(token);
 
-    bot.set_my_commands(Command::bot_commands())
+    // The commands are described in every language, English by default
+    bot.set_my_commands(localized_commands(Language::En))
         .await
         .expect("Failed to set bot commands");
+    for language in Language::ALL.into_iter().filter(|language| *language != Language::En) {
+        bot.set_my_commands(localized_commands(language))
+            .language_code(language.code())
+            .await
+            .expect("Failed to set bot commands");
+    }
 
-    DATABASE.create_table().await;
+    // The administrators also see their commands in the menu of their chat
+    let mut admin_commands = localized_commands(Language::En);
+    admin_commands.extend(AdminCommand::bot_commands());
+    for chat_id in CONFIG.get_admins() {
+        let scope = BotCommandScope::Chat { chat_id: Recipient::Id(chat_id) };
+        if let Err(e) = bot.set_my_commands(admin_commands.clone()).scope(scope).await {
+            log::warn!("Failed to set the commands of the administrator {chat_id}: {e}");
+        }
+    }
 
     tokio::spawn(poll_time(bot.clone()));
 
-    // Create the dispatcher
+    // The health and the metrics of the bot are served if an address is set
+    if let Some(address) = CONFIG.get_monitoring_address() {
+        monitoring::serve(address, &DATABASE).expect("Failed to start the monitoring server");
+    }
+
+    // Create the dispatcher, the commands of the administrators are only read in their chats
     let handler = dptree::entry()
+        .inspect(|| METRICS.add_update())
+        .branch(
+            Update::filter_message()
+                .filter(|msg: Message| is_bot_admin(msg.chat.id))
+                .filter_command::<AdminCommand>()
+                .endpoint(admin_handler),
+        )
         .branch(Update::filter_message().endpoint(message_handler))
         .branch(Update::filter_callback_query().endpoint(callback_handler));
 
-    Dispatcher::builder(bot, handler)
+    let mut dispatcher = Dispatcher::builder(bot.clone(), handler)
         .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+        .build();
+    // With a webhook url, the updates are received by a webhook instead of long polling
+    match CONFIG.get_webhook() {
+        Some(webhook) => {
+            let (address, listener) = webhook::serve(&webhook).expect("Failed to start the webhook");
+            webhook::register(&bot, &webhook).await.expect("Failed to set the webhook");
+            log::info!("Listen the updates on {address} for {}", webhook.url);
+            let error_handler = LoggingErrorHandler::with_custom_text("An error from the webhook");
+            dispatcher.dispatch_with_listener(listener, error_handler).await;
+
+            // The webhook is deleted when the bot stops
+            if let Err(e) = webhook::unregister(&bot).await {
+                log::error!("Failed to delete the webhook: {e}");
+            }
+        }
+        None => dispatcher.dispatch().await,
+    }
 }
 
+/// This function describe the commands in a language, in English if not translated
+fn localized_commands(language: Language) -> Vec<BotCommand> {
+    Command::bot_commands()
+        .into_iter()
+        .map(|command| {
+            let key = format!("command-{}", command.command.trim_start_matches('/'));
+            match language.get(&key) {
+                Some(description) => BotCommand::new(command.command, description),
+                None => command,
+            }
+        })
+        .collect()
+}
+
+/// This function send the reminders of the trackers when they are due
+///
+/// The time of the last reminder of each tracker is saved, so the reminders missed while
+/// the bot was offline are sent at startup. The loop sleeps until the next reminder
+/// or until a user change his schedule.
 async fn poll_time(bot: Bot) {
     loop {
+        let now = Utc::now();
+        let mut next_wake = now + chrono::Duration::hours(1);
+        METRICS.tick_scheduler(now);
 
-        let users = DATABASE.get_hours().await;
+        let trackers = DATABASE.get_all_trackers().await.unwrap_or_else(|e| {
+            log::error!("Failed to get trackers: {e}");
+            vec![]
+        });
 
-        let now = Utc::now();
-        let offset = Local::now().hour()-now.hour();
-        let hour = now.hour()+offset;
-
-
-
-        for user in users {
-            if now.minute() == 0 {
-                if user.1 as u32 == hour {
-                    let msg_id = send_day_rank_message(
-                        bot.clone(),
-                        user.0,
-                        std::option::Option::from(now),
-                        None,
-                    ).await;
-                    let usr = DATABASE.get_user_by_chat_id(user.0).await.unwrap();
-                    let rank_day = RankDay::new(usr, now, msg_id);
-                    tokio::spawn(DATABASE.add_rank_day(rank_day));
+        for tracker in trackers {
+            let chat_id = tracker.get_user().get_chat_id();
+            let name = tracker.get_name();
+            match send_due_prompts(bot.clone(), tracker, now).await {
+                Ok(next) => next_wake = next_wake.min(next),
+                Err(e) => log::error!("Failed to send reminders of {name} to {chat_id}: {e}"),
+            }
+        }
+
+        let sleep = (next_wake - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        tokio::select! {
+            _ = tokio::time::sleep(sleep) => {}
+            _ = SCHEDULE_CHANGED.notified() => {}
+        }
+    }
+}
+
+/// This function send the reminders and the nudge due for a tracker
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker
+/// * `now` - The actual time
+///
+/// # Return
+/// Return the time of the next reminder or nudge of the tracker
+async fn send_due_prompts(
+    bot: Bot,
+    tracker: Tracker,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, Box<dyn Error + Send + Sync>> {
+    let tracker_id = tracker.get_id();
+    let user = tracker.get_user();
+    let timezone = user.get_timezone();
+
+    // Trackers created before the scheduler saved the last reminder
+    let mut last_prompt = match tracker.get_last_prompt() {
+        Some(last_prompt) => last_prompt,
+        None => {
+            let last_prompt = DATABASE.get_last_rank_time(tracker_id).await?.unwrap_or(now);
+            DATABASE.set_last_prompt(tracker_id, last_prompt).await?;
+            last_prompt
+        }
+    };
+
+    // Send every reminder missed, at most for the last days
+    for due in schedule::due_occurrences(timezone, tracker.get_hour(), tracker.get_minute(), last_prompt, now) {
+        // The days paused or stopped are skipped, they aren't sent when the reminders restart
+        let date = due.with_timezone(&timezone).date_naive();
+        if user.is_reminded(date) {
+            send_prompt(bot.clone(), tracker.clone(), due).await?;
+
+            // The recaps are sent with the reminder, a recap which fails isn't sent again
+            for recap in Recap::due_on(date, user.get_recap_weekly(), user.get_recap_monthly()) {
+                if let Err(e) = send_recap(bot.clone(), &tracker, recap, date).await {
+                    log::error!("Failed to send the recap of {} to {}: {e}", tracker.get_name(), user.get_chat_id());
                 }
             }
         }
+        DATABASE.set_last_prompt(tracker_id, due).await?;
+        last_prompt = due;
+    }
+    let mut due = schedule::next_occurrence(timezone, tracker.get_hour(), tracker.get_minute(), last_prompt.max(now));
+
+    // Send the nudge once after each reminder
+    if let Some((hour, minute)) = tracker.get_nudge() {
+        let since = tracker.get_last_nudge().map_or(last_prompt, |last_nudge| last_nudge.max(last_prompt));
+        let mut nudge = schedule::next_occurrence(timezone, hour, minute, since);
+        if nudge <= now {
+            if user.is_reminded(now.with_timezone(&timezone).date_naive()) {
+                send_nudge(bot.clone(), tracker.clone(), now).await?;
+            }
+            DATABASE.set_last_nudge(tracker_id, now).await?;
+            nudge = schedule::next_occurrence(timezone, hour, minute, now);
+        }
+        due = due.min(nudge);
+    }
+
+    Ok(due)
+}
+
+/// This function send a reminder and add its rank day, if the local day isn't ranked yet
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker to remind
+/// * `time` - The time of the reminder
+async fn send_prompt(bot: Bot, tracker: Tracker, time: DateTime<Utc>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // At most one rank day by local date
+    let date = time.with_timezone(&tracker.get_user().get_timezone()).date_naive();
+    if DATABASE.get_rank_day_on(&tracker, date).await?.is_some() {
+        return Ok(());
+    }
+
+    send_new_rank_day(bot, tracker, time).await
+}
+
+/// This function send the recap of the past period of a tracker
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker
+/// * `recap` - The recap to send
+/// * `date` - The local date of the recap
+async fn send_recap(bot: Bot, tracker: &Tracker, recap: Recap, date: NaiveDate) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tracker_id = tracker.get_id();
+    let lang = tracker.get_user().get_language();
+    let scale = tracker.get_scale();
+    let (from, to) = recap.bounds(date);
+    let (previous_from, previous_to) = recap.previous(date);
+    let days = DATABASE.get_ranks_between(tracker_id, from, to).await?;
+
+    let (title, previous_name) = match recap {
+        Recap::Weekly => (
+            lang.format("recap-week", &[("from", &lang.day(from)), ("to", &lang.day(to))]),
+            lang.text("recap-previous-week"),
+        ),
+        Recap::Monthly => (
+            lang.format("recap-month", &[("month", &lang.month_year(from))]),
+            lang.month_year(previous_from),
+        ),
+    };
+    let mut message = format!("{title}{}\n\n{}\n", tracker_suffix(tracker), recap::squares(&days, from, to, &scale));
+
+    // Average compared with the previous period
+    match Stats::compute(&days, from, to, from, to, &scale).get_average() {
+        Some(average) => {
+            message.push_str(format!("\n{}", lang.format("average", &[("average", &format!("{average:.2}"))])).as_str());
+            let previous_days = DATABASE.get_ranks_between(tracker_id, previous_from, previous_to).await?;
+            if let Some(previous_average) = Stats::compute(&previous_days, previous_from, previous_to, previous_from, previous_to, &scale).get_average() {
+                let difference = format!("{:+.2}", average - previous_average);
+                message.push_str(format!(" {}", lang.format("compared-to", &[("difference", &difference), ("period", &previous_name)])).as_str());
+            }
+        }
+        None => message.push_str(format!("\n{}", lang.text("average-none")).as_str()),
+    }
+
+    let comments = DATABASE.get_comments_between(tracker_id, from, to).await?;
+    if !comments.is_empty() {
+        message.push_str(format!("\n\n{}", lang.text("recap-comments")).as_str());
+        for (date, comment) in comments {
+            message.push_str(format!("\n{}: {comment}", lang.short_day(date)).as_str());
+        }
+    }
+
+    send::to_chat(tracker.get_user().get_chat_id(), |chat_id| bot.send_message(chat_id, message.clone()).send(), migrate_chat).await?;
+    Ok(())
+}
+
+/// This function send again the last rank day message of a tracker not ranked in the last 24 hours
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker of the rank day
+/// * `now` - The actual time
+async fn send_nudge(bot: Bot, tracker: Tracker, now: DateTime<Utc>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let since = now - chrono::Duration::hours(24);
+    if let Some(rank_day) = DATABASE.get_unranked_since(&tracker, since).await? {
+        resend_rank_day(bot, rank_day).await?;
+    }
+    Ok(())
+}
+
+/// This function add the rank day of a tracker and send its message to choose a rank
+///
+/// The rank day is saved first, the buttons of the message need its id. Nothing is sent if
+/// the date already has a rank day (ex: added by /rate meanwhile). The rank day added is
+/// deleted if the message can't be sent, the reminder is sent again later.
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker of the rank day
+/// * `time` - The time of the reminder of the day
+async fn send_new_rank_day(bot: Bot, tracker: Tracker, time: DateTime<Utc>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut rank_day = RankDay::new(tracker, time, MessageId(0));
+    match DATABASE.add_rank_day(rank_day.clone()).await? {
+        Some(id) => rank_day.set_id(id),
+        None => return Ok(()),
+    }
+
+    match send_day_rank_message(bot, &rank_day, None).await {
+        Ok(id_msg) => {
+            METRICS.add_prompt();
+            Ok(DATABASE.set_id_msg(rank_day.get_id(), id_msg).await?)
+        }
+        Err(e) => {
+            DATABASE.delete_rank_day(rank_day.get_id()).await?;
+            record_send_failure(rank_day.get_user().get_chat_id(), e.as_ref()).await;
+            Err(e)
+        }
+    }
+}
+
+/// This function send again the message of a rank day not ranked
+///
+/// The new message replace the old one, which is deleted.
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `rank_day` - The rank day
+async fn resend_rank_day(bot: Bot, rank_day: RankDay) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = rank_day.get_user().get_chat_id();
+    let new_id_msg = match send_day_rank_message(bot.clone(), &rank_day, None).await {
+        Ok(new_id_msg) => new_id_msg,
+        Err(e) => {
+            record_send_failure(chat_id, e.as_ref()).await;
+            return Err(e);
+        }
+    };
+    METRICS.add_prompt();
+    DATABASE.set_id_msg(rank_day.get_id(), new_id_msg).await?;
+    bot.delete_message(chat_id, rank_day.get_id_msg()).await.ok();
+    Ok(())
+}
+
+/// This function save on the user a message which can't be sent to him anymore, his reminders
+/// are stopped until he starts the bot again
+///
+/// # Arguments
+/// * `chat_id` - The chat of the message
+/// * `error` - The error of the message, the temporary errors are only counted in the metrics
+async fn record_send_failure(chat_id: ChatId, error: &(dyn Error + Send + Sync + 'static)) {
+    METRICS.add_send_failure();
+    let Some(error) = error.downcast_ref::<RequestError>().filter(|e| send::is_permanent_failure(e)) else {
+        return;
+    };
+    log::warn!("Chat {chat_id} can't receive messages anymore, its reminders are stopped: {error}");
+    if let Err(e) = DATABASE.set_send_failure(chat_id, Some(error.to_string())).await {
+        log::error!("Failed to stop the reminders of {chat_id}: {e}");
+    }
+}
+
+/// This function move a group which became a supergroup to its new chat id
+///
+/// # Arguments
+/// * `chat_id` - The old id of the chat
+/// * `new_chat_id` - The id of the supergroup
+async fn migrate_chat(chat_id: ChatId, new_chat_id: ChatId) {
+    match DATABASE.set_chat_id(chat_id, new_chat_id).await {
+        Ok(()) => SCHEDULE_CHANGED.notify_one(),
+        Err(e) => log::error!("Failed to move chat {chat_id} to {new_chat_id}: {e}"),
+    }
+}
+
+/// This function parse a time written as HH:MM (or HHhMM, or HH)
+///
+/// # Return
+/// Return the hour and the minute or None if the time isn't valid
+fn parse_time(text: &str) -> Option<(u8, u8)> {
+    let text = text.trim().to_lowercase();
+    let (hour, minute) = match text.split_once([':', 'h']) {
+        Some((hour, "")) => (hour, "0"),
+        Some((hour, minute)) => (hour, minute),
+        None => (text.as_str(), "0"),
+    };
+    let hour = hour.trim().parse::<u8>().ok()?;
+    let minute = minute.trim().parse::<u8>().ok()?;
+    if hour < 24 && minute < 60 {
+        Some((hour, minute))
+    } else {
+        None
+    }
+}
 
-        task::sleep(Duration::from_secs(60)).await;
+/// This function give the name of a tracker to add to the messages, empty for the default tracker
+fn tracker_suffix(tracker: &Tracker) -> String {
+    match tracker.get_name().as_str() {
+        DEFAULT_TRACKER => String::new(),
+        name => format!(" ({name})"),
     }
 }
 
-fn get_month(month: u32) -> &'static str {
-    match month {
-        1 => "January",
-        2 => "February",
-        3 => "March",
-        4 => "April",
-        5 => "May",
-        6 => "June",
-        7 => "July",
-        8 => "August",
-        9 => "September",
-        10 => "October",
-        11 => "November",
-        12 => "December",
-        _ => panic!("Month not found"),
+/// This function find the tracker of a command, named by the first word of its arguments
+///
+/// # Arguments
+/// * `chat_id` - The chat id of the user
+/// * `args` - The arguments of the command (ex: "sleep 08:00" or "08:00")
+///
+/// # Return
+/// Return the tracker named and the other arguments, or the first tracker of the user and all
+/// the arguments if the first word isn't one of his trackers. None if the user has no tracker.
+async fn select_tracker(chat_id: ChatId, args: &str) -> Result<Option<(Tracker, String)>, DbError> {
+    let mut trackers = DATABASE.get_trackers(chat_id).await?;
+    let args = args.trim();
+    let (first, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if let Some(position) = trackers.iter().position(|tracker| tracker.get_name() == first) {
+        return Ok(Some((trackers.swap_remove(position), rest.trim().to_string())));
+    }
+    match trackers.is_empty() {
+        true => Ok(None),
+        false => Ok(Some((trackers.swap_remove(0), args.to_string()))),
     }
 }
 
@@ -126,119 +624,194 @@ fn get_month(month: u32) -> &'static str {
 /// # Arguments
 ///
 /// * `bot` - The bot for sending message
-/// * `chat_id` - The chat id for sending message
-/// * `utc_time` - The date for the evaluated day (if None, the date is get from the rank day list)
+/// * `rank_day` - The evaluated day, with its tracker (question and scale)
 /// * `id_msg` - The message id for edit message (if None, the message is send)
 ///
 /// # Return
 /// Return the message id of the message send or edit
 async fn send_day_rank_message(
     bot: Bot,
-    chat_id: ChatId,
-    utc_time: Option<DateTime<Utc>>,
+    rank_day: &RankDay,
     id_msg: Option<MessageId>,
-) -> MessageId {
-    // Define utc time from rank day time if None
-    let time;
-    let time_in_db = match id_msg {
-        Some(id_msg) => DATABASE.get_time(chat_id, id_msg).await.unwrap_or(Utc::now()),
-        None => Utc::now(),
-    };
-    match utc_time {
-        Some(utc_time) => time = utc_time,
-        None => time = time_in_db,
-    };
+) -> Result<MessageId, Box<dyn Error + Send + Sync>> {
+    let tracker = rank_day.get_tracker();
+    let chat_id = tracker.get_user().get_chat_id();
+    let lang = tracker.get_user().get_language();
+    let scale = tracker.get_scale();
+    let time = rank_day.get_time().with_timezone(&tracker.get_user().get_timezone());
 
     // Format message with date
-    let day = time.day();
-    let weekday = time.weekday();
-    let month = get_month(time.month());
-    let year = time.year();
-    let text_message = format!("How drunk are you {weekday} {day} {month} {year} ?");
+    let mut text_message = format!("{} {} ?", tracker.get_question(), lang.date(time.date_naive()));
+
+    // In a group, the members who already ranked their day
+    if !chat_id.is_user() {
+        match DATABASE.get_group_rank_members(rank_day.get_id()).await {
+            Ok(members) if !members.is_empty() => {
+                text_message.push_str(format!("\n\n{}", lang.format("ranked-by", &[("members", &members.join(", "))])).as_str());
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to get the members of rank day {}: {e}", rank_day.get_id()),
+        }
+    }
 
-    // Create callback keyboard with ranks
+    // Create callback keyboard with the labels of the scale, the action is the rank of the day
     let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
-    for rank in LIST_RANK.clone().chunks(6) {
-        let row = rank
+    let ranks: Vec<(u8, String)> = scale.get_ranks().into_iter().zip(scale.get_labels()).collect();
+    for chunk in ranks.chunks(6) {
+        let row = chunk
             .iter()
-            .map(|&rank| InlineKeyboardButton::callback(rank.to_owned(), rank.to_owned()))
-            .collect();
+            .map(|(rank, label)| CallbackAction::Rank { rank_day: rank_day.get_id(), rank: *rank }.button(label.to_owned()))
+            .collect::<Result<Vec<_>, _>>()?;
         keyboard.push(row);
     }
+    let keyboard = InlineKeyboardMarkup::new(keyboard);
 
-    // Send message or edit message
+    // Send message or edit message, again after the temporary errors
     let msg = match id_msg {
         Some(id_msg) => {
             // Edit message
-            bot.edit_message_text(chat_id, id_msg, text_message)
-                .reply_markup(InlineKeyboardMarkup::new(keyboard.clone()))
-                .await
+            let edit = send::with_retry(|| {
+                bot.edit_message_text(chat_id, id_msg, text_message.clone())
+                    .reply_markup(keyboard.clone())
+                    .send()
+            });
+            match edit.await {
+                // The message is already up to date (ex: a member ranked again his day)
+                Err(RequestError::Api(ApiError::MessageNotModified)) => return Ok(id_msg),
+                msg => msg?,
+            }
         }
         None => {
-            // Send message
-            bot.send_message(chat_id, text_message)
-                .reply_markup(InlineKeyboardMarkup::new(keyboard.clone()))
-                .await
+            // Send message, to the new id of a group which became a supergroup
+            send::to_chat(
+                chat_id,
+                |chat_id| bot.send_message(chat_id, text_message.clone()).reply_markup(keyboard.clone()).send(),
+                migrate_chat,
+            )
+            .await?
         }
     };
-
-    // Return message id or 0 if error
-    match msg {
-        Ok(message) => message.id,
-        Err(e) => {
-            eprintln!("Failed to send or edit message : {:?}", e);
-            MessageId(0)
-        }
-    }
+    Ok(msg.id)
 }
 
-/// This function send a message with the rank for the evaluated day
+/// This function edit the message of a rank day to show its rank
 ///
 /// # Arguments
 /// * `bot` - The bot for sending message
-/// * `chat_id` - The chat id for sending message
-/// * `utc_time` - The date for the evaluated day
-/// * `id_msg` - The message id for edit message
-/// * `rank` - The rank for the evaluated day
+/// * `rank_day` - The evaluated day, ranked
 ///
 /// # Return
-/// Return the message id of the message send or edit
+/// Return the message id of the message edit
 async fn send_day_message(
     bot: Bot,
-    chat_id: ChatId,
-    utc_time: DateTime<Utc>,
-    id_msg: MessageId,
-    rank: String,
-) -> MessageId {
+    rank_day: &RankDay,
+) -> Result<MessageId, Box<dyn Error + Send + Sync>> {
+    let tracker = rank_day.get_tracker();
+    let lang = tracker.get_user().get_language();
+    let rank = rank_day.get_rank().map(|rank| tracker.get_scale().label(rank)).unwrap_or_default();
+    let comment = rank_day.get_comment();
+
     // Format message with date and rank
-    let day = utc_time.day();
-    let weekday = utc_time.weekday();
-    let month = get_month(utc_time.month());
-    let year = utc_time.year();
-    let text_message =
-        format!("{weekday} {day} {month} {year} you put a {rank} on the Picole Pixel");
+    let mut text_message = lang.format(
+        "day-ranked",
+        &[("date", &lang.date(rank_day.get_date())), ("rank", &rank), ("tracker", &tracker_suffix(&tracker))],
+    );
+    if let Some(comment) = &comment {
+        text_message.push_str(format!("\n\n{}", lang.format("day-comment", &[("comment", comment)])).as_str());
+    }
 
     // Create callback keyboard
-    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
-    let list = ["Edit", "Add comment"];
-    for choice in list.chunks(2) {
-        let row = choice
-            .iter()
-            .map(|&rank| InlineKeyboardButton::callback(rank.to_owned(), rank.to_owned()))
-            .collect();
-
-        keyboard.push(row);
+    let rank_day_id = rank_day.get_id();
+    let mut row = vec![CallbackAction::Edit { rank_day: rank_day_id }.button(lang.text("button-edit"))?];
+    match comment {
+        Some(_) => {
+            row.push(CallbackAction::Comment { rank_day: rank_day_id }.button(lang.text("button-edit-comment"))?);
+            row.push(CallbackAction::DeleteComment { rank_day: rank_day_id }.button(lang.text("button-delete-comment"))?);
+        }
+        None => row.push(CallbackAction::Comment { rank_day: rank_day_id }.button(lang.text("button-add-comment"))?),
     }
 
     // Edit message
     let msg = bot
-        .edit_message_text(chat_id, id_msg, text_message)
-        .reply_markup(InlineKeyboardMarkup::new(keyboard.clone()))
-        .await;
-    msg.unwrap().id
+        .edit_message_text(tracker.get_user().get_chat_id(), rank_day.get_id_msg(), text_message)
+        .reply_markup(InlineKeyboardMarkup::new(vec![row]))
+        .await?;
+    Ok(msg.id)
+}
+
+/// This function check if the sender of a message can change the settings of its chat
+///
+/// In a private chat the user can, in a group only the administrators can (also when they
+/// send the message as the group).
+async fn is_admin(bot: &Bot, msg: &Message) -> Result<bool, RequestError> {
+    if msg.sender_chat().is_some_and(|sender| sender.id == msg.chat.id) {
+        return Ok(true);
+    }
+    match msg.from() {
+        Some(member) => is_member_admin(bot, msg.chat.id, member.id).await,
+        None => Ok(false),
+    }
+}
+
+/// This function check if a member of a chat can change its settings
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `chat_id` - The chat, a private chat or a group
+/// * `member` - The member
+async fn is_member_admin(bot: &Bot, chat_id: ChatId, member: UserId) -> Result<bool, RequestError> {
+    if chat_id.is_user() {
+        return Ok(true);
+    }
+    Ok(bot.get_chat_member(chat_id, member).await?.kind.is_privileged())
+}
+
+/// This function tell the user his request failed
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id of the user
+/// * `lang` - The language of the chat
+/// * `error` - The error of the request
+async fn report_error(bot: Bot, chat_id: ChatId, lang: Language, error: Box<dyn Error + Send + Sync>) {
+    log::error!("Request of {chat_id} failed: {error}");
+    let message = lang.text("error");
+    if let Err(e) = bot.send_message(chat_id, message).await {
+        log::error!("Failed to report error to {chat_id}: {e}");
+    }
+}
+
+/// Save the current names of the user of a chat, they can change between two interactions
+///
+/// # Arguments
+/// * `chat` - The chat of the interaction
+async fn refresh_user(chat: &Chat) {
+    if let Err(e) = DATABASE.refresh_user(&User::from_chat(chat, None, CONFIG.get_default_timezone())).await {
+        log::error!("Failed to refresh the names of {}: {e}", chat.id);
+    }
 }
 
-/// Handler for message
+/// This function find the language of a chat: the language chosen by its user, else the
+/// language of the Telegram user who wrote
+///
+/// # Arguments
+/// * `chat_id` - The chat
+/// * `from` - The Telegram user who sent the message or used the button
+async fn chat_language(chat_id: ChatId, from: Option<&teloxide::types::User>) -> Language {
+    match DATABASE.get_user_by_chat_id(chat_id).await {
+        Ok(Some(user)) => user.get_language(),
+        Ok(None) => from
+            .and_then(|from| from.language_code.as_deref())
+            .and_then(Language::from_code)
+            .unwrap_or_default(),
+        Err(e) => {
+            log::error!("Failed to get the language of {chat_id}: {e}");
+            Language::default()
+        }
+    }
+}
+
+/// Handler for message, the errors are reported to the user
 ///
 /// # Arguments
 /// * `bot` - The bot
@@ -246,44 +819,112 @@ async fn send_day_message(
 /// * `me` - The bot information
 ///
 /// # Return
-/// Return Ok if no error
+/// Return Ok, the errors are already reported
 async fn message_handler(
     bot: Bot,
     msg: Message,
     me: Me,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = msg.chat.id;
+    refresh_user(&msg.chat).await;
+    let lang = chat_language(chat_id, msg.from()).await;
+    if let Err(e) = handle_message(bot.clone(), msg, me, lang).await {
+        report_error(bot, chat_id, lang, e).await;
+    }
+    Ok(())
+}
+
+/// This function handle a message
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `msg` - The message received
+/// * `me` - The bot information
+/// * `lang` - The language of the chat
+///
+/// # Return
+/// Return Ok if no error
+async fn handle_message(
+    bot: Bot,
+    msg: Message,
+    me: Me,
+    lang: Language,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     if let Some(text) = msg.text() {
-        match BotCommands::parse(text, me.username()) {
+        // A text which isn't a command can be the comment of a rank day
+        if let Some(from) = msg.from().filter(|_| !text.starts_with('/')) {
+            let pending = PENDING_COMMENTS.lock().unwrap().remove(&(msg.chat.id, from.id));
+            if let Some(rank_day_id) = pending {
+                save_comment(bot, msg.chat.id, lang, rank_day_id, Some(text.to_string())).await?;
+                return Ok(());
+            }
+        }
+
+        let command = Command::parse(text, me.username());
+
+        // In a group, the settings are shared by the members and changed by the administrators
+        if let Ok(command) = &command {
+            if !msg.chat.is_private() && command.is_private() {
+                bot.send_message(msg.chat.id, lang.text("private-only")).await?;
+                return Ok(());
+            }
+            if command.is_setting() && !is_admin(&bot, &msg).await? {
+                bot.send_message(msg.chat.id, lang.text(ADMIN_ONLY)).await?;
+                return Ok(());
+            }
+        }
+
+        match command {
             // Handle the command `/start`
             Ok(Command::Start) => {
-                // Create user and add to user list
-                let username = msg.chat.username().expect("Username not found");
-                println!("Chat id: {} is with {}", msg.chat.id, username);
-                let user = User::new(msg.chat.id, username.to_string(), None);
+                // Create user and add to user list, a group is registered with its title
+                let user = User::from_chat(&msg.chat, msg.from(), CONFIG.get_default_timezone());
+                log::debug!("Chat {} started the bot", msg.chat.id);
 
-                let user_exist = DATABASE.add_user(user.clone()).await;
+                let user_exist = DATABASE.add_user(user.clone()).await?;
+
+                // Start again the reminders paused or stopped
+                let saved = DATABASE.get_user_by_chat_id(user.get_chat_id()).await?;
+                let restarted = saved.is_some_and(|saved| {
+                    saved.is_stopped() || saved.get_paused_until().is_some() || saved.get_send_failure().is_some()
+                });
+                if restarted {
+                    DATABASE.set_send_failure(user.get_chat_id(), None).await?;
+                    DATABASE.set_paused_until(user.get_chat_id(), None).await?;
+                }
+
+                // Every user has at least the default tracker
+                let mut trackers = DATABASE.get_trackers(user.get_chat_id()).await?;
+                if trackers.is_empty() {
+                    let mut tracker = Tracker::new(user.clone(), DEFAULT_TRACKER.to_string(), CONFIG.get_default_scale(), CONFIG.get_default_hour());
+                    if let Some(id) = DATABASE.add_tracker(&tracker).await? {
+                        tracker.set_id(id);
+                        trackers.push(tracker);
+                    }
+                }
+                SCHEDULE_CHANGED.notify_one();
+
+                let name = user.get_display_name();
                 match user_exist {
                     true => {
-                        tokio::spawn({
-                            let bot = bot.clone();
-                            let msg = format!("Hi {} ! You already start this bot", user.get_username());
-                            let id = user.get_chat_id();
-                            async move {
-                                bot.send_message(id, msg).await
-                            }
-                        });
+                        let mut msg = lang.format("start-again", &[("name", &name)]);
+                        if restarted {
+                            msg.push_str(format!("\n{}", lang.text("start-restarted")).as_str());
+                        }
+                        bot.send_message(user.get_chat_id(), msg).await?;
                     }
                     false => {
-                        tokio::spawn({
-                            let bot = bot.clone();
-                            let mut msg = format!("Welcome to Picole Pixel {} !\n", user.get_username());
-                            msg.push_str(format!("\nYou will receive every day at {}h00 a message to evaluate your day.", user.get_hour()).as_str());
-                            msg.push_str("\nYou can change the hour with the command /sethour {h} (ex: /sethour 22)." );
-                            let id = user.get_chat_id();
-                            async move {
-                                bot.send_message(id, msg).await
-                            }
-                        });
+                        let time = trackers.first().map(Tracker::get_time).unwrap_or_default();
+                        let mut msg = format!("{}\n", lang.format("welcome", &[("name", &name)]));
+                        match user.get_chat_id().is_user() {
+                            true => msg.push_str(format!("\n{}", lang.format("welcome-time", &[("time", &time)])).as_str()),
+                            false => msg.push_str(format!("\n{}", lang.format("welcome-group-time", &[("time", &time)])).as_str()),
+                        }
+                        msg.push_str(format!("\n{}", lang.text("welcome-settime")).as_str());
+                        msg.push_str(format!("\n{}", lang.format("welcome-timezone", &[("timezone", &user.get_timezone().name())])).as_str());
+                        msg.push_str(format!("\n{}", lang.text("welcome-newtracker")).as_str());
+                        msg.push_str(format!("\n{}", lang.text("welcome-language")).as_str());
+                        bot.send_message(user.get_chat_id(), msg).await?;
                     }
                 }
 
@@ -291,100 +932,1585 @@ async fn message_handler(
 
             // Handle the command `/help`
             Ok(Command::Help) => {
-                bot.send_message(msg.chat.id, Command::descriptions().to_string())
-                    .await?;
+                let mut message = lang.text("help");
+                for command in localized_commands(lang) {
+                    message.push_str(format!("\n{} — {}", command.command, command.description).as_str());
+                }
+                bot.send_message(msg.chat.id, message).await?;
             }
 
-            // Handle the command `/sethour`
-            Ok(Command::SetHour(hour)) => {
-                let result = DATABASE.set_hour(msg.chat.id, hour).await;
-                match result {
-                    Ok(_) => {
-                        let message = format!("You will receive your message for evaluate your day at {}h00 now", hour);
-                        bot.send_message(msg.chat.id, message)
-                            .await?;
+            // Handle the command `/settime`
+            Ok(Command::SetTime(args)) => {
+                let (tracker, time) = match select_tracker(msg.chat.id, &args).await? {
+                    Some(selected) => selected,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
                     }
-                    Err(_) => {
-                        bot.send_message(msg.chat.id, "Error when set hour".to_string())
-                            .await?;
+                };
+                let message = match parse_time(&time) {
+                    Some((hour, minute)) => {
+                        DATABASE.set_time(tracker.get_id(), hour, minute).await?;
+                        let time = format!("{hour:02}:{minute:02}");
+                        lang.format("settime-done", &[("time", &time), ("tracker", &tracker_suffix(&tracker))])
                     }
-                }
+                    None => lang.text("settime-invalid"),
+                };
+                SCHEDULE_CHANGED.notify_one();
+                bot.send_message(msg.chat.id, message).await?;
             }
 
+            // Handle the command `/setnudge`
+            Ok(Command::SetNudge(args)) => {
+                let (tracker, time) = match select_tracker(msg.chat.id, &args).await? {
+                    Some(selected) => selected,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                let nudge = match time.trim() {
+                    "off" | "" => Some(None),
+                    time => parse_time(time).map(Some),
+                };
+                let message = match nudge {
+                    Some(nudge) => {
+                        DATABASE.set_nudge(tracker.get_id(), nudge).await?;
+                        match nudge {
+                            Some((hour, minute)) => {
+                                let time = format!("{hour:02}:{minute:02}");
+                                lang.format("setnudge-done", &[("time", &time), ("tracker", &tracker_suffix(&tracker))])
+                            }
+                            None => lang.format("setnudge-off", &[("tracker", &tracker_suffix(&tracker))]),
+                        }
+                    }
+                    None => lang.text("setnudge-invalid"),
+                };
+                SCHEDULE_CHANGED.notify_one();
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/settimezone`
+            Ok(Command::SetTimezone(timezone)) => {
+                match timezone.trim().parse::<Tz>() {
+                    Ok(timezone) => {
+                        let result = DATABASE.set_timezone(msg.chat.id, timezone).await;
+                        let message = match result {
+                            Ok(_) => lang.format("timezone-done", &[("timezone", &timezone.name())]),
+                            Err(DbError::UserNotFound(_)) => lang.text("start-first"),
+                            Err(e) => return Err(e.into()),
+                        };
+                        SCHEDULE_CHANGED.notify_one();
+                        bot.send_message(msg.chat.id, message).await?;
+                    }
+                    Err(_) => {
+                        send_timezone_suggestions(bot, msg.chat.id, lang, timezone).await?;
+                    }
+                }
+            }
+
+            // Handle the command `/setscale`
+            Ok(Command::SetScale(args)) => {
+                let (tracker, text) = match select_tracker(msg.chat.id, &args).await? {
+                    Some(selected) => selected,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                let message = match Scale::parse(&text) {
+                    Some(scale) => {
+                        DATABASE.set_scale(tracker.get_id(), &scale).await?;
+                        lang.format("setscale-done", &[("labels", &scale.get_labels().join(" ")), ("tracker", &tracker_suffix(&tracker))])
+                    }
+                    None => lang.format("setscale-invalid", &[("max", &scale::MAX_LABELS)]),
+                };
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/setquestion`
+            Ok(Command::SetQuestion(args)) => {
+                let (tracker, question) = match select_tracker(msg.chat.id, &args).await? {
+                    Some(selected) => selected,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                let question = match question.trim().trim_end_matches('?').trim() {
+                    "" => Tracker::default_question(&tracker.get_name(), lang),
+                    question => question.to_string(),
+                };
+                DATABASE.set_question(tracker.get_id(), &question).await?;
+                let message = lang.format("setquestion-done", &[("question", &question), ("tracker", &tracker_suffix(&tracker))]);
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/newtracker`
+            Ok(Command::NewTracker(args)) => {
+                let user = match DATABASE.get_user_by_chat_id(msg.chat.id).await? {
+                    Some(user) => user,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                let args = args.trim();
+                let (name, time) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                let time = match time.trim() {
+                    "" => Some((CONFIG.get_default_hour(), 0)),
+                    time => parse_time(time),
+                };
+
+                let message = match (Tracker::is_valid_name(name), time) {
+                    (false, _) => lang.format("newtracker-invalid-name", &[("max", &tracker::MAX_NAME_LENGTH)]),
+                    (true, None) => lang.text("newtracker-invalid-time"),
+                    (true, Some(_)) if DATABASE.get_trackers(msg.chat.id).await?.len() >= MAX_TRACKERS => {
+                        lang.format("newtracker-too-many", &[("max", &MAX_TRACKERS)])
+                    }
+                    (true, Some((hour, minute))) => {
+                        let mut tracker = Tracker::new(user, name.to_string(), CONFIG.get_default_scale(), hour);
+                        tracker.set_minute(minute);
+                        match DATABASE.add_tracker(&tracker).await? {
+                            Some(_) => lang.format("newtracker-done", &[("name", &name), ("time", &tracker.get_time())]),
+                            None => lang.format("newtracker-exists", &[("name", &name)]),
+                        }
+                    }
+                };
+                SCHEDULE_CHANGED.notify_one();
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/trackers`
+            Ok(Command::Trackers) => {
+                let trackers = DATABASE.get_trackers(msg.chat.id).await?;
+                if trackers.is_empty() {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                }
+                let mut message = lang.text("trackers");
+                for tracker in trackers {
+                    message.push_str(format!("\n\n{}: {} ... ?", tracker.get_name(), tracker.get_question()).as_str());
+                    message.push_str(format!("\n{}", lang.format("trackers-time", &[("time", &tracker.get_time())])).as_str());
+                    if let Some((hour, minute)) = tracker.get_nudge() {
+                        let time = format!("{hour:02}:{minute:02}");
+                        message.push_str(format!(", {}", lang.format("trackers-nudge", &[("time", &time)])).as_str());
+                    }
+                    message.push_str(format!("\n{}", lang.format("trackers-ranks", &[("labels", &tracker.get_scale().get_labels().join(" "))])).as_str());
+                }
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/deletetracker`
+            Ok(Command::DeleteTracker(name)) => {
+                let trackers = DATABASE.get_trackers(msg.chat.id).await?;
+                let tracker = trackers.iter().find(|tracker| tracker.get_name() == name.trim());
+                match tracker {
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("deletetracker-not-found")).await?;
+                    }
+                    Some(_) if trackers.len() == 1 => {
+                        bot.send_message(msg.chat.id, lang.text("deletetracker-only")).await?;
+                    }
+                    Some(tracker) => {
+                        let days = DATABASE.get_ranks(tracker.get_id()).await?.len();
+                        let message = lang.format("deletetracker-confirm", &[("name", &tracker.get_name()), ("days", &days)]);
+                        let keyboard = vec![vec![
+                            CallbackAction::DeleteTracker { tracker: Some(tracker.get_id()) }.button(lang.text("button-delete"))?,
+                            CallbackAction::DeleteTracker { tracker: None }.button(lang.text("button-cancel"))?,
+                        ]];
+                        bot.send_message(msg.chat.id, message)
+                            .reply_markup(InlineKeyboardMarkup::new(keyboard))
+                            .await?;
+                    }
+                }
+            }
+
+            // Handle the command `/recap`
+            Ok(Command::Recap(choice)) => {
+                let Some(user) = DATABASE.get_user_by_chat_id(msg.chat.id).await? else {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                };
+                let recaps = match choice.trim().to_lowercase().as_str() {
+                    "" => None,
+                    "weekly" => Some((true, false)),
+                    "monthly" => Some((false, true)),
+                    "both" => Some((true, true)),
+                    "off" => Some((false, false)),
+                    _ => {
+                        bot.send_message(msg.chat.id, lang.text("recap-invalid")).await?;
+                        return Ok(());
+                    }
+                };
+                let (weekly, monthly) = match recaps {
+                    Some((weekly, monthly)) => {
+                        DATABASE.set_recaps(msg.chat.id, weekly, monthly).await?;
+                        (weekly, monthly)
+                    }
+                    None => (user.get_recap_weekly(), user.get_recap_monthly()),
+                };
+                let message = match (weekly, monthly) {
+                    (true, true) => lang.text("recap-both"),
+                    (true, false) => lang.text("recap-weekly"),
+                    (false, true) => lang.text("recap-monthly"),
+                    (false, false) => lang.text("recap-off"),
+                };
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/pause`
+            Ok(Command::Pause(days)) => {
+                let days = match days.trim() {
+                    "" => Some(DEFAULT_PAUSE_DAYS),
+                    days => days.parse::<u32>().ok().filter(|days| (1..=MAX_PAUSE_DAYS).contains(days)),
+                };
+                let Some(days) = days else {
+                    bot.send_message(msg.chat.id, lang.format("pause-invalid", &[("max", &MAX_PAUSE_DAYS)])).await?;
+                    return Ok(());
+                };
+                let Some(user) = DATABASE.get_user_by_chat_id(msg.chat.id).await? else {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                };
+
+                // The reminders start again the day after the pause
+                let today = Utc::now().with_timezone(&user.get_timezone()).date_naive();
+                let paused_until = today + chrono::Duration::days(i64::from(days));
+                DATABASE.set_paused_until(msg.chat.id, Some(paused_until)).await?;
+                SCHEDULE_CHANGED.notify_one();
+
+                let message = lang.format("pause-done", &[("date", &lang.date(paused_until))]);
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/stop`
+            Ok(Command::Stop) => {
+                if DATABASE.get_user_by_chat_id(msg.chat.id).await?.is_none() {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                }
+                DATABASE.set_stopped(msg.chat.id, true).await?;
+                SCHEDULE_CHANGED.notify_one();
+                bot.send_message(msg.chat.id, lang.text("stop-done")).await?;
+            }
+
+            // Handle the command `/deleteme`
+            Ok(Command::DeleteMe) => {
+                if DATABASE.get_user_by_chat_id(msg.chat.id).await?.is_none() {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                }
+                let keyboard = vec![vec![
+                    CallbackAction::DeleteAccount { confirm: true }.button(lang.text("button-delete"))?,
+                    CallbackAction::DeleteAccount { confirm: false }.button(lang.text("button-cancel"))?,
+                ]];
+                bot.send_message(msg.chat.id, lang.text("deleteme-confirm"))
+                    .reply_markup(InlineKeyboardMarkup::new(keyboard))
+                    .await?;
+            }
+
+            // Handle the command `/language`
+            Ok(Command::Language(code)) => {
+                if DATABASE.get_user_by_chat_id(msg.chat.id).await?.is_none() {
+                    bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                    return Ok(());
+                }
+                match code.trim() {
+                    "" => {
+                        let row = Language::ALL
+                            .into_iter()
+                            .map(|language| CallbackAction::Language(language).button(language.name()))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        bot.send_message(msg.chat.id, lang.text("language-choose"))
+                            .reply_markup(InlineKeyboardMarkup::new(vec![row]))
+                            .await?;
+                    }
+                    code => match Language::from_code(code) {
+                        Some(language) => {
+                            DATABASE.set_language(msg.chat.id, language).await?;
+                            bot.send_message(msg.chat.id, language.text("language-done")).await?;
+                        }
+                        None => {
+                            bot.send_message(msg.chat.id, lang.text("language-invalid")).await?;
+                        }
+                    },
+                }
+            }
+
+            // Handle the command `/rate`
+            Ok(Command::Rate(args)) => {
+                let (tracker, date) = match select_tracker(msg.chat.id, &args).await? {
+                    Some(selected) => selected,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                let today = Utc::now().with_timezone(&tracker.get_user().get_timezone()).date_naive();
+                let yesterday = today.pred_opt().expect("Date out of range");
+
+                match date.trim() {
+                    "" => {
+                        rate_day(bot.clone(), tracker.clone(), yesterday).await?;
+                        send_rate_picker(bot, tracker, yesterday).await?;
+                    }
+                    date => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                        Ok(date) if date <= today => rate_day(bot, tracker, date).await?,
+                        Ok(_) => {
+                            bot.send_message(msg.chat.id, lang.text("rate-future")).await?;
+                        }
+                        Err(_) => {
+                            bot.send_message(msg.chat.id, lang.text("rate-invalid")).await?;
+                        }
+                    },
+                }
+            }
+
+            // Handle the command `/pixel`, the mosaic of the members in a group
+            Ok(Command::Pixel(args)) => match msg.chat.is_private() {
+                true => send_pixel(bot, msg.chat.id, lang, args).await?,
+                false => send_group_pixel(bot, msg.chat.id, lang, args).await?,
+            },
+
+            // Handle the command `/stats`, the statistics of the members in a group
+            Ok(Command::Stats(args)) => match msg.chat.is_private() {
+                true => send_stats(bot, msg.chat.id, lang, args).await?,
+                false => send_group_stats(bot, msg.chat.id, lang, args).await?,
+            },
+
+            // Handle the command `/groupstats`
+            Ok(Command::GroupStats(args)) => match msg.chat.is_private() {
+                true => {
+                    bot.send_message(msg.chat.id, lang.text("group-only")).await?;
+                }
+                false => send_group_stats(bot, msg.chat.id, lang, args).await?,
+            },
+
+            // Handle the command `/export`
+            Ok(Command::Export(args)) => {
+                send_export(bot, msg.chat.id, lang, args).await?;
+            }
+
+            // Handle the command `/import`
+            Ok(Command::Import(args)) => {
+                let tracker = match select_tracker(msg.chat.id, &args).await? {
+                    Some((tracker, _)) => tracker,
+                    None => {
+                        bot.send_message(msg.chat.id, lang.text("start-first")).await?;
+                        return Ok(());
+                    }
+                };
+                IMPORT_TRACKERS.lock().unwrap().insert(msg.chat.id, tracker.get_id());
+
+                let message = lang.format("import-send", &[("tracker", &tracker_suffix(&tracker))]);
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // Handle the command `/cancel`
+            Ok(Command::Cancel) => {
+                let pending = msg.from().and_then(|from| PENDING_COMMENTS.lock().unwrap().remove(&(msg.chat.id, from.id)));
+                let import_tracker = IMPORT_TRACKERS.lock().unwrap().remove(&msg.chat.id);
+                let pending_import = PENDING_IMPORTS.lock().unwrap().remove(&msg.chat.id);
+                let message = match (pending, pending_import.is_some() || import_tracker.is_some()) {
+                    (Some(_), _) => lang.text("cancel-comment"),
+                    (None, true) => lang.text("cancel-import"),
+                    (None, false) => lang.text("cancel-nothing"),
+                };
+                bot.send_message(msg.chat.id, message).await?;
+            }
+
+            // In a group, the other messages aren't for the bot
+            Err(_) if msg.chat.is_private() => {
+                bot.send_message(msg.chat.id, lang.text("unknown-command")).await?;
+            }
+            Err(_) => {}
+        }
+    } else if let Some(document) = msg.document().filter(|_| msg.chat.is_private()) {
+        // The only files expected are the imports, in a private chat
+        read_import(bot, msg.chat.id, lang, document.clone()).await?;
+    }
+
+    Ok(())
+}
+
+/// This function send the message to rank a past day
+///
+/// If the day already has a message not ranked, this message is replaced by the new one.
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker of the day
+/// * `date` - The local date to rank
+///
+/// # Return
+/// Return Ok if no error
+async fn rate_day(
+    bot: Bot,
+    tracker: Tracker,
+    date: NaiveDate,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = tracker.get_user().get_chat_id();
+    let lang = tracker.get_user().get_language();
+    let timezone = tracker.get_user().get_timezone();
+    match DATABASE.get_rank_day_on(&tracker, date).await? {
+        Some(rank_day) => {
+            if let Some(rank) = rank_day.get_rank() {
+                let rank = tracker.get_scale().label(rank);
+                let message = lang.format(
+                    "rate-already",
+                    &[("rank", &rank), ("date", &lang.date(date)), ("tracker", &tracker_suffix(&tracker))],
+                );
+                bot.send_message(chat_id, message).await?;
+                return Ok(());
+            }
+            resend_rank_day(bot, rank_day).await?;
+        }
+        None => {
+            let time = schedule::occurrence_on(timezone, tracker.get_hour(), tracker.get_minute(), date);
+            send_new_rank_day(bot, tracker, time).await?;
+        }
+    }
+    Ok(())
+}
+
+/// This function send a keyboard with the last days not ranked of a tracker
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `tracker` - The tracker of the days
+/// * `except` - A date not proposed (ex: the day already sent)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_rate_picker(
+    bot: Bot,
+    tracker: Tracker,
+    except: NaiveDate,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let timezone = tracker.get_user().get_timezone();
+    let lang = tracker.get_user().get_language();
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    let ranked: Vec<NaiveDate> = DATABASE
+        .get_ranks(tracker.get_id())
+        .await?
+        .into_iter()
+        .map(|(date, _)| date)
+        .collect();
+
+    let dates: Vec<NaiveDate> = (1..=RATE_PICKER_DAYS)
+        .map(|days| today - chrono::Duration::days(days))
+        .filter(|date| *date != except && !ranked.contains(date))
+        .collect();
+    if dates.is_empty() {
+        return Ok(());
+    }
+
+    // Create callback keyboard with two dates by row
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+    for chunk in dates.chunks(2) {
+        let row = chunk
+            .iter()
+            .map(|date| {
+                CallbackAction::Rate { tracker: tracker.get_id(), date: *date }.button(lang.short_day(*date))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        keyboard.push(row);
+    }
+
+    let message = lang.format("rate-picker", &[("tracker", &tracker_suffix(&tracker))]);
+    bot.send_message(tracker.get_user().get_chat_id(), message)
+        .reply_markup(InlineKeyboardMarkup::new(keyboard))
+        .await?;
+    Ok(())
+}
+
+/// This function send the Picole Pixel of a year as an image
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id for sending message
+/// * `lang` - The language of the chat
+/// * `args` - The tracker (if empty, the first one) and the year (if empty, the current year)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_pixel(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    args: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (tracker, year) = match select_tracker(chat_id, &args).await? {
+        Some(selected) => selected,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
+
+    let year = match year.trim() {
+        "" => Utc::now().with_timezone(&tracker.get_user().get_timezone()).year(),
+        year => match year.parse::<i32>() {
+            Ok(year) => year,
             Err(_) => {
-                bot.send_message(msg.chat.id, "Command not fount !").await?;
+                bot.send_message(chat_id, lang.text("pixel-invalid-year")).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let username = tracker.get_user().get_display_name();
+    let title = match tracker.get_name().as_str() {
+        DEFAULT_TRACKER => username.clone(),
+        name => format!("{username} {name}"),
+    };
+    let days = DATABASE.get_ranks(tracker.get_id()).await?;
+    let image = pixel::render(&title, year, &days, &tracker.get_scale())?;
+
+    bot.send_photo(chat_id, InputFile::memory(image).file_name("picole_pixel.png"))
+        .caption(lang.format("pixel-caption", &[("year", &year), ("name", &username), ("tracker", &tracker_suffix(&tracker))]))
+        .await?;
+    Ok(())
+}
+
+/// This function send the statistics of a period
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id for sending message
+/// * `lang` - The language of the chat
+/// * `args` - The tracker (if empty, the first one) and the period: month, year or all (if empty, the current month)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_stats(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    args: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (tracker, period) = match select_tracker(chat_id, &args).await? {
+        Some(selected) => selected,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
+
+    let period = match Period::parse(&period) {
+        Some(period) => period,
+        None => {
+            bot.send_message(chat_id, lang.text("stats-invalid")).await?;
+            return Ok(());
+        }
+    };
+
+    let tracker_id = tracker.get_id();
+    let first = match DATABASE.get_first_rank_date(tracker_id).await? {
+        Some(first) => first,
+        None => {
+            bot.send_message(chat_id, lang.format("stats-none", &[("tracker", &tracker_suffix(&tracker))])).await?;
+            return Ok(());
+        }
+    };
+
+    let today = Utc::now().with_timezone(&tracker.get_user().get_timezone()).date_naive();
+    let (from, to) = period.bounds(today, first);
+    let days = DATABASE.get_ranks_between(tracker_id, from, to).await?;
+    let scale = tracker.get_scale();
+    let prompted = tracker.get_last_prompt().map(|time| time.with_timezone(&tracker.get_user().get_timezone()).date_naive());
+    let last_due = stats::last_due(today, prompted, days.last().map(|(date, _)| *date));
+    let stats = Stats::compute(&days, from, to, first, last_due, &scale);
+
+    let mut message = format!("{}\n", lang.format("stats-title", &[("period", &period_name(period, from, lang)), ("tracker", &tracker_suffix(&tracker))]));
+
+    match stats.get_average() {
+        Some(average) => {
+            message.push_str(format!("\n{}", lang.format("average", &[("average", &format!("{average:.2}"))])).as_str());
+
+            // Comparison with the previous period
+            if let Some((previous_from, previous_to)) = period.previous(today) {
+                let previous_days = DATABASE.get_ranks_between(tracker_id, previous_from, previous_to).await?;
+                let previous = Stats::compute(&previous_days, previous_from, previous_to, first, previous_to, &scale);
+                if let Some(previous_average) = previous.get_average() {
+                    let label = match period {
+                        Period::Month => lang.month_year(previous_from),
+                        _ => previous_from.year().to_string(),
+                    };
+                    let difference = format!("{:+.2}", average - previous_average);
+                    message.push_str(format!(" {}", lang.format("compared-to", &[("difference", &difference), ("period", &label)])).as_str());
+                }
             }
         }
+        None => message.push_str(format!("\n{}", lang.text("average-none")).as_str()),
+    }
+
+    message.push_str(format!("\n{}", lang.format("stats-rated", &[("days", &stats.get_rated())])).as_str());
+    message.push_str(format!("\n{}", lang.format("stats-missed", &[("days", &stats.get_missed())])).as_str());
+    message.push_str(format!("\n{}", lang.format("stats-streak", &[("days", &stats.get_longest_streak())])).as_str());
+    if let Some((weekday, average)) = stats.get_best_weekday() {
+        let average = format!("{average:.2}");
+        message.push_str(format!("\n{}", lang.format("stats-best", &[("weekday", &lang.weekday(weekday)), ("average", &average)])).as_str());
+    }
+    if let Some((weekday, average)) = stats.get_worst_weekday() {
+        let average = format!("{average:.2}");
+        message.push_str(format!("\n{}", lang.format("stats-worst", &[("weekday", &lang.weekday(weekday)), ("average", &average)])).as_str());
+    }
+
+    // Distribution of the ranks, the bars are relative to the most used rank
+    message.push_str(format!("\n\n{}", lang.text("stats-distribution")).as_str());
+    let distribution = stats.get_distribution();
+    let max = distribution.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1);
+    for (rank, count) in distribution.iter().rev() {
+        let bar = "▇".repeat((count * 10).div_ceil(max) as usize);
+        message.push_str(format!("\n{} {bar} {count}", scale.label(*rank)).as_str());
     }
 
+    bot.send_message(chat_id, message).await?;
     Ok(())
 }
 
-async fn callback_handler(
+/// This function name a period of statistics (ex: "of March 2024")
+///
+/// # Arguments
+/// * `period` - The period
+/// * `from` - The first date of the period
+/// * `lang` - The language of the name
+fn period_name(period: Period, from: NaiveDate, lang: Language) -> String {
+    match period {
+        Period::Month => lang.format("period-month", &[("month", &lang.month_year(from))]),
+        Period::Year => lang.format("period-year", &[("year", &from.year())]),
+        Period::All => lang.format("period-all", &[("date", &lang.date(from))]),
+    }
+}
+
+/// This function gather the ranks of the members of a group by member
+///
+/// # Arguments
+/// * `ranks` - The ranks of the members, sorted by date
+///
+/// # Return
+/// Return the last name and the ranks of each member, by order of first rank
+fn group_by_member(ranks: Vec<(UserId, String, NaiveDate, u8)>) -> Vec<(String, Vec<(NaiveDate, u8)>)> {
+    let mut positions: HashMap<UserId, usize> = HashMap::new();
+    let mut members: Vec<(String, Vec<(NaiveDate, u8)>)> = Vec::new();
+    for (member_id, name, date, rank) in ranks {
+        match positions.get(&member_id) {
+            Some(&position) => {
+                members[position].0 = name;
+                members[position].1.push((date, rank));
+            }
+            None => {
+                positions.insert(member_id, members.len());
+                members.push((name, vec![(date, rank)]));
+            }
+        }
+    }
+    members
+}
+
+/// This function send the Picole Pixel of a year of the members of a group as an image
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id of the group
+/// * `lang` - The language of the chat
+/// * `args` - The tracker (if empty, the first one) and the year (if empty, the current year)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_group_pixel(
     bot: Bot,
-    cbq: CallbackQuery,
+    chat_id: ChatId,
+    lang: Language,
+    args: String,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    if let Some(ref rank) = cbq.data {
-        bot.answer_callback_query(&cbq.id).await?;
+    let (tracker, year) = match select_tracker(chat_id, &args).await? {
+        Some(selected) => selected,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
 
-        let chat_id = cbq.message.clone().unwrap().chat.id;
-
-        if let Some(Message { id, chat, .. }) = cbq.message {
-            if rank == "Edit" {
-                /********
-                 * EDIT *
-                 ********/
-
-                // If edit, send message with rank day list
-                tokio::spawn(send_day_rank_message(
-                    bot.clone(),
-                    chat.id,
-                    None,
-                    std::option::Option::from(id),
-                ));
-
-                // Clear rank in rank day list
-                tokio::spawn(DATABASE.update_rank(
-                    chat.id,
-                    id,
-                    None,
-                ));
-            } else if rank == "Add comment" {
-                /***********
-                 * COMMENT *
-                 ***********/
-
-                // TODO: Add comment
-            } else {
-                /********
-                 * RANK *
-                 ********/
-                let rank = rank.parse::<u8>().unwrap();
-                // Update rank in rank day list
-                tokio::spawn(DATABASE.update_rank(
-                    chat.id,
-                    id,
-                    Option::from(rank),
-                ));
-
-                // Send message with rank
-                let time = DATABASE.get_time(chat_id, id).await.expect("Failed to get time");
-                tokio::spawn(send_day_message(
-                    bot.clone(),
-                    chat.id,
-                    time,
-                    id,
-                    rank.to_string(),
-                ));
+    let year = match year.trim() {
+        "" => Utc::now().with_timezone(&tracker.get_user().get_timezone()).year(),
+        year => match year.parse::<i32>() {
+            Ok(year) => year,
+            Err(_) => {
+                bot.send_message(chat_id, lang.text("pixel-invalid-year")).await?;
+                return Ok(());
             }
+        },
+    };
+
+    let (from, to) = match (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            bot.send_message(chat_id, lang.text("pixel-invalid-year")).await?;
             return Ok(());
         }
+    };
+    let members = group_by_member(DATABASE.get_group_ranks_between(tracker.get_id(), from, to).await?);
+    if members.is_empty() {
+        bot.send_message(chat_id, lang.format("group-pixel-none", &[("year", &year), ("tracker", &tracker_suffix(&tracker))])).await?;
+        return Ok(());
     }
 
-    log::info!(
-        "Callback query from {:?} with data {:?}",
-        cbq.from,
-        cbq.data
+    let group = tracker.get_user().get_display_name();
+    let title = match tracker.get_name().as_str() {
+        DEFAULT_TRACKER => group.clone(),
+        name => format!("{group} {name}"),
+    };
+    let image = pixel::render_group(&title, year, &members, &tracker.get_scale())?;
+
+    bot.send_photo(chat_id, InputFile::memory(image).file_name("picole_pixel.png"))
+        .caption(lang.format(
+            "group-pixel-caption",
+            &[("year", &year), ("name", &group), ("tracker", &tracker_suffix(&tracker)), ("members", &members.len())],
+        ))
+        .await?;
+    Ok(())
+}
+
+/// This function send the statistics of the members of a group for a period
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id of the group
+/// * `lang` - The language of the chat
+/// * `args` - The tracker (if empty, the first one) and the period: month, year or all (if empty, the current month)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_group_stats(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    args: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (tracker, period) = match select_tracker(chat_id, &args).await? {
+        Some(selected) => selected,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
+
+    let period = match Period::parse(&period) {
+        Some(period) => period,
+        None => {
+            bot.send_message(chat_id, lang.text("group-stats-invalid")).await?;
+            return Ok(());
+        }
+    };
+
+    let today = Utc::now().with_timezone(&tracker.get_user().get_timezone()).date_naive();
+    let ranks = DATABASE
+        .get_group_ranks_between(tracker.get_id(), NaiveDate::from_ymd_opt(1, 1, 1).unwrap_or_default(), today)
+        .await?;
+    let first = match ranks.first() {
+        Some((_, _, first, _)) => *first,
+        None => {
+            bot.send_message(chat_id, lang.format("group-stats-none", &[("tracker", &tracker_suffix(&tracker))])).await?;
+            return Ok(());
+        }
+    };
+
+    let (from, to) = period.bounds(today, first);
+    let scale = tracker.get_scale();
+    let in_period: Vec<u8> = ranks
+        .iter()
+        .filter(|(_, _, date, _)| *date >= from && *date <= to)
+        .map(|(_, _, _, rank)| *rank)
+        .collect();
+
+    let mut message = format!("{}\n", lang.format("group-stats-title", &[("period", &period_name(period, from, lang)), ("tracker", &tracker_suffix(&tracker))]));
+    if in_period.is_empty() {
+        message.push_str(format!("\n{}", lang.text("group-stats-empty")).as_str());
+        bot.send_message(chat_id, message).await?;
+        return Ok(());
+    }
+    let average = in_period.iter().map(|rank| *rank as f64).sum::<f64>() / in_period.len() as f64;
+    let average = format!("{average:.2}");
+    message.push_str(format!("\n{}", lang.format("group-stats-average", &[("average", &average), ("ranks", &in_period.len())])).as_str());
+
+    // The members who ranked a day of the period, the best average first
+    let mut members: Vec<(String, Stats)> = group_by_member(ranks)
+        .into_iter()
+        .map(|(name, days)| {
+            let first = days.first().map_or(first, |(date, _)| *date);
+            let stats = Stats::compute(&days, from, to, first, to, &scale);
+            (name, stats)
+        })
+        .filter(|(_, stats)| stats.get_rated() > 0)
+        .collect();
+    members.sort_by(|a, b| b.1.get_average().partial_cmp(&a.1.get_average()).unwrap_or(std::cmp::Ordering::Equal));
+
+    message.push_str(format!("\n\n{}", lang.format("group-stats-members", &[("members", &members.len())])).as_str());
+    for (name, stats) in members {
+        let average = format!("{:.2}", stats.get_average().unwrap_or_default());
+        let line = lang.format(
+            "group-stats-member",
+            &[("name", &name), ("average", &average), ("days", &stats.get_rated()), ("streak", &stats.get_longest_streak())],
+        );
+        message.push_str(format!("\n{line}").as_str());
+    }
+
+    bot.send_message(chat_id, message).await?;
+    Ok(())
+}
+
+/// This function send the rank days of a tracker as a document
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id for sending message
+/// * `lang` - The language of the chat
+/// * `args` - The tracker (the first one by default), the format (csv or json, csv by default) and the year (every year by default)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_export(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    args: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (tracker, args) = match select_tracker(chat_id, &args).await? {
+        Some(selected) => selected,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
+
+    let mut format = export::Format::Csv;
+    let mut year = None;
+    for arg in args.split_whitespace() {
+        if let Some(arg_format) = export::Format::parse(arg) {
+            format = arg_format;
+        } else if let Ok(arg_year) = arg.parse::<i32>() {
+            year = Some(arg_year);
+        } else {
+            bot.send_message(chat_id, lang.text("export-invalid")).await?;
+            return Ok(());
+        }
+    }
+
+    let rank_days = DATABASE.get_rank_days(&tracker, year).await?;
+    if rank_days.is_empty() {
+        bot.send_message(chat_id, lang.text("export-none")).await?;
+        return Ok(());
+    }
+
+    let content = export::serialize(format, &rank_days)?;
+    let name = match tracker.get_name().as_str() {
+        DEFAULT_TRACKER => "picole_pixel".to_string(),
+        name => format!("picole_pixel_{name}"),
+    };
+    let file_name = match year {
+        Some(year) => format!("{name}_{year}.{}", format.extension()),
+        None => format!("{name}.{}", format.extension()),
+    };
+    bot.send_document(chat_id, InputFile::memory(content).file_name(file_name))
+        .caption(lang.format("export-caption", &[("days", &rank_days.len()), ("tracker", &tracker_suffix(&tracker))]))
+        .await?;
+    Ok(())
+}
+
+/// This function read a file to import and send a preview of the import to confirm
+///
+/// The days are imported in the tracker chosen with /import, or in the first tracker.
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id of the user importing
+/// * `lang` - The language of the user
+/// * `document` - The file sent by the user
+///
+/// # Return
+/// Return Ok if no error
+async fn read_import(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    document: Document,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tracker_id = IMPORT_TRACKERS.lock().unwrap().remove(&chat_id);
+    let tracker = match tracker_id {
+        Some(tracker_id) => DATABASE.get_tracker_by_id(tracker_id).await?,
+        None => select_tracker(chat_id, "").await?.map(|(tracker, _)| tracker),
+    };
+    let tracker = match tracker {
+        Some(tracker) => tracker,
+        None => {
+            bot.send_message(chat_id, lang.text("start-first")).await?;
+            return Ok(());
+        }
+    };
+
+    if document.file.size > MAX_IMPORT_SIZE {
+        bot.send_message(chat_id, lang.text("import-too-big")).await?;
+        return Ok(());
+    }
+
+    let file = bot.get_file(&document.file.id).await?;
+    let mut content = Vec::new();
+    bot.download_file(&file.path, &mut content).await?;
+
+    // The days after today in the timezone of the user can't be ranked yet
+    let today = Utc::now().with_timezone(&tracker.get_user().get_timezone()).date_naive();
+    let import = match import::parse(&content, &tracker.get_scale(), today) {
+        Ok(import) => import,
+        Err(e) => {
+            bot.send_message(chat_id, lang.format("import-invalid", &[("error", &e.text(lang))])).await?;
+            return Ok(());
+        }
+    };
+
+    // Compare with the days already ranked
+    let existing: HashMap<NaiveDate, u8> = DATABASE
+        .get_ranks(tracker.get_id())
+        .await?
+        .into_iter()
+        .collect();
+    let mut new = 0;
+    let mut same = 0;
+    let mut conflicts = 0;
+    for row in &import.rows {
+        match existing.get(&row.date) {
+            None => new += 1,
+            Some(rank) if *rank == row.rank => same += 1,
+            Some(_) => conflicts += 1,
+        }
+    }
+
+    let mut message = lang.format(
+        "import-preview",
+        &[("days", &import.rows.len()), ("tracker", &tracker_suffix(&tracker)), ("new", &new), ("same", &same), ("conflicts", &conflicts)],
     );
+    if !import.errors.is_empty() {
+        message.push_str(format!("\n\n{}", lang.format("import-ignored", &[("lines", &import.errors.len())])).as_str());
+        for (position, error) in import.errors.iter().take(5) {
+            message.push_str(format!("\n- {}: {}", position.text(lang), error.text(lang)).as_str());
+        }
+        if import.errors.len() > 5 {
+            message.push_str("\n- ...");
+        }
+    }
+
+    if import.rows.is_empty() {
+        bot.send_message(chat_id, message).await?;
+        return Ok(());
+    }
+
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+    if conflicts > 0 {
+        keyboard.push(vec![CallbackAction::Import(ImportChoice::Replace).button(lang.text("button-import-replace"))?]);
+        keyboard.push(vec![CallbackAction::Import(ImportChoice::Keep).button(lang.text("button-import-keep"))?]);
+    } else {
+        keyboard.push(vec![CallbackAction::Import(ImportChoice::Keep).button(lang.text("button-import"))?]);
+    }
+    keyboard.push(vec![CallbackAction::Import(ImportChoice::Cancel).button(lang.text("button-cancel"))?]);
+
+    PENDING_IMPORTS.lock().unwrap().insert(chat_id, (tracker.get_id(), import.rows));
+    bot.send_message(chat_id, message)
+        .reply_markup(InlineKeyboardMarkup::new(keyboard))
+        .await?;
+    Ok(())
+}
+
+/// This function send a keyboard with the timezones matching a search
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id for sending message
+/// * `lang` - The language of the chat
+/// * `search` - The timezone searched (ex: Zurich)
+///
+/// # Return
+/// Return Ok if no error
+async fn send_timezone_suggestions(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    search: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let search = search.trim().to_lowercase().replace(' ', "_");
+    let timezones: Vec<&Tz> = TZ_VARIANTS
+        .iter()
+        .filter(|tz| !search.is_empty() && tz.name().to_lowercase().contains(&search))
+        .take(8)
+        .collect();
+
+    if timezones.is_empty() {
+        bot.send_message(chat_id, lang.text("timezone-not-found")).await?;
+        return Ok(());
+    }
+
+    // Create callback keyboard with a timezone by row
+    let keyboard: Vec<Vec<InlineKeyboardButton>> = timezones
+        .iter()
+        .map(|tz| Ok(vec![CallbackAction::Timezone(**tz).button(tz.name())?]))
+        .collect::<Result<_, callback::CallbackError>>()?;
+
+    bot.send_message(chat_id, lang.text("timezone-choose"))
+        .reply_markup(InlineKeyboardMarkup::new(keyboard))
+        .await?;
+    Ok(())
+}
+
+/// This function save the comment of a rank day and update the day message
+///
+/// # Arguments
+/// * `bot` - The bot for sending message
+/// * `chat_id` - The chat id of the rank day
+/// * `lang` - The language of the chat
+/// * `rank_day_id` - The id of the rank day
+/// * `comment` - The new comment (if None, the comment is deleted)
+///
+/// # Return
+/// Return Ok if no error
+async fn save_comment(
+    bot: Bot,
+    chat_id: ChatId,
+    lang: Language,
+    rank_day_id: i64,
+    comment: Option<String>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    DATABASE.update_comment(rank_day_id, comment.clone()).await?;
+
+    // Show the comment on the day message if the day is already ranked
+    if let Some(rank_day) = DATABASE.get_rank_day(rank_day_id).await? {
+        if rank_day.get_rank().is_some() {
+            send_day_message(bot.clone(), &rank_day).await?;
+        }
+    }
+
+    let message = match comment {
+        Some(_) => lang.text("comment-saved"),
+        None => lang.text("comment-deleted"),
+    };
+    bot.send_message(chat_id, message).await?;
+    Ok(())
+}
+
+/// This function get the rank day of a button, if it belongs to the chat of the button
+///
+/// # Return
+/// Return None if the rank day doesn't exist anymore or belongs to another chat
+async fn get_chat_rank_day(chat_id: ChatId, rank_day_id: i64) -> Result<Option<RankDay>, DbError> {
+    let rank_day = DATABASE.get_rank_day(rank_day_id).await?;
+    Ok(rank_day.filter(|rank_day| rank_day.get_user().get_chat_id() == chat_id))
+}
+
+/// Alert shown for a button which can't be read (ex: sent by an older version of the bot)
+const EXPIRED_BUTTON: &str = "alert-expired";
+
+/// Alert shown for a button of a rank day which doesn't exist anymore
+const DAY_NOT_FOUND: &str = "alert-day-not-found";
+
+/// Alert shown for a comment button used in a group
+const COMMENT_PRIVATE_ONLY: &str = "alert-comment-private-only";
+
+/// Alert shown for a button of a tracker which doesn't exist anymore
+const TRACKER_NOT_FOUND: &str = "alert-tracker-not-found";
+
+/// Alert shown to a member of a group using a button changing the settings of the group
+const ADMIN_ONLY: &str = "admin-only";
+
+/// This function check if a chat is an administrator of the bot
+fn is_bot_admin(chat_id: ChatId) -> bool {
+    CONFIG.get_admins().contains(&chat_id)
+}
+
+/// Handler for the commands of the administrators of the bot, the errors are reported to them
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `msg` - The message received
+/// * `command` - The command of the message
+///
+/// # Return
+/// Return Ok, the errors are already reported
+async fn admin_handler(
+    bot: Bot,
+    msg: Message,
+    command: AdminCommand,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = msg.chat.id;
+    if let Err(e) = handle_admin_command(bot.clone(), chat_id, command).await {
+        report_error(bot, chat_id, Language::En, e).await;
+    }
+    Ok(())
+}
+
+/// This function handle a command of an administrator of the bot, its messages are in English
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `chat_id` - The chat of the administrator
+/// * `command` - The command
+///
+/// # Return
+/// Return Ok if no error
+async fn handle_admin_command(
+    bot: Bot,
+    chat_id: ChatId,
+    command: AdminCommand,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match command {
+        // Handle the command `/users`
+        AdminCommand::Users => {
+            // Today is the local date of each user
+            let counts = DATABASE.get_user_counts(Utc::now()).await?;
+            let mut message = format!("{} users", counts.users);
+            message.push_str(format!("\n{} active today", counts.active).as_str());
+            message.push_str(format!("\n{} paused", counts.paused).as_str());
+            message.push_str(format!("\n{} stopped", counts.stopped).as_str());
+            bot.send_message(chat_id, message).await?;
+        }
+
+        // Handle the command `/broadcast`
+        AdminCommand::Broadcast(text) => {
+            let text = text.trim();
+            if text.is_empty() {
+                bot.send_message(chat_id, "Write the message to send (ex: /broadcast New version !)").await?;
+                return Ok(());
+            }
+            let chats = DATABASE.get_receiving_chats().await?;
+            PENDING_BROADCASTS.lock().unwrap().insert(chat_id, text.to_string());
+
+            // The message is shown as the users will receive it
+            bot.send_message(chat_id, text).await?;
+            let keyboard = vec![vec![
+                CallbackAction::Broadcast { confirm: true }.button(format!("Send to {} chats", chats.len()))?,
+                CallbackAction::Broadcast { confirm: false }.button("Cancel")?,
+            ]];
+            bot.send_message(chat_id, "Send this message to every chat whose reminders aren't stopped ?")
+                .reply_markup(InlineKeyboardMarkup::new(keyboard))
+                .await?;
+        }
+
+        // Handle the command `/user`
+        AdminCommand::User(args) => {
+            let Ok(user_chat) = args.trim().parse::<i64>().map(ChatId) else {
+                bot.send_message(chat_id, "Chat id not valid (ex: /user 1234)").await?;
+                return Ok(());
+            };
+            let Some(user) = DATABASE.get_user_by_chat_id(user_chat).await? else {
+                bot.send_message(chat_id, format!("No user in the chat {user_chat}")).await?;
+                return Ok(());
+            };
+
+            let mut message = format!("{} (chat {user_chat})", user.get_display_name());
+            if let Some(username) = user.get_username() {
+                message.push_str(format!("\nUsername: @{username}").as_str());
+            }
+            message.push_str(format!("\nTimezone: {}", user.get_timezone().name()).as_str());
+            message.push_str(format!("\nLanguage: {}", user.get_language().name()).as_str());
+            if let Some(paused_until) = user.get_paused_until() {
+                message.push_str(format!("\nPaused until {paused_until}").as_str());
+            }
+            if user.is_stopped() {
+                message.push_str("\nReminders stopped");
+            }
+            if let Some(send_failure) = user.get_send_failure() {
+                message.push_str(format!("\nLast message not sent: {send_failure}").as_str());
+            }
+            message.push_str(
+                format!("\nRecaps: weekly {}, monthly {}", user.get_recap_weekly(), user.get_recap_monthly()).as_str(),
+            );
+
+            for tracker in DATABASE.get_trackers(user_chat).await? {
+                let days = DATABASE.get_ranks(tracker.get_id()).await?.len();
+                message.push_str(format!("\n\nTracker {}: {} ... ?", tracker.get_name(), tracker.get_question()).as_str());
+                message.push_str(format!("\nEvery day at {}", tracker.get_time()).as_str());
+                if let Some((hour, minute)) = tracker.get_nudge() {
+                    message.push_str(format!(", nudge at {hour:02}:{minute:02}").as_str());
+                }
+                message.push_str(format!("\nScale: {}", tracker.get_scale()).as_str());
+                message.push_str(format!("\nRanked days: {days}").as_str());
+                if let Some(last_prompt) = tracker.get_last_prompt() {
+                    message.push_str(format!("\nLast reminder: {}", last_prompt.format("%Y-%m-%d %H:%M UTC")).as_str());
+                }
+            }
+            bot.send_message(chat_id, message).await?;
+        }
+
+        // Handle the command `/forceprompt`
+        AdminCommand::ForcePrompt(args) => {
+            let Ok(user_chat) = args.trim().parse::<i64>().map(ChatId) else {
+                bot.send_message(chat_id, "Chat id not valid (ex: /forceprompt 1234)").await?;
+                return Ok(());
+            };
+            let trackers = DATABASE.get_trackers(user_chat).await?;
+            if trackers.is_empty() {
+                bot.send_message(chat_id, format!("No user in the chat {user_chat}")).await?;
+                return Ok(());
+            }
+
+            // The message of today is sent again if it isn't ranked, else it is sent for the first time
+            let now = Utc::now();
+            let mut message = format!("Reminders of the chat {user_chat}:");
+            for tracker in trackers {
+                let name = tracker.get_name();
+                let today = now.with_timezone(&tracker.get_user().get_timezone()).date_naive();
+                let result = match DATABASE.get_rank_day_on(&tracker, today).await? {
+                    Some(rank_day) if rank_day.get_rank().is_some() => {
+                        message.push_str(format!("\n{name}: already ranked today").as_str());
+                        continue;
+                    }
+                    Some(rank_day) => resend_rank_day(bot.clone(), rank_day).await,
+                    None => send_new_rank_day(bot.clone(), tracker, now).await,
+                };
+                match result {
+                    Ok(()) => message.push_str(format!("\n{name}: sent").as_str()),
+                    Err(e) => message.push_str(format!("\n{name}: not sent, {e}").as_str()),
+                }
+            }
+            bot.send_message(chat_id, message).await?;
+        }
+    }
+    Ok(())
+}
+
+/// This function send a message to every chat whose reminders aren't stopped, slowly enough
+/// for the limits of Telegram, then report the result to the administrator
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `admin` - The chat of the administrator who sent the broadcast
+/// * `text` - The message to send
+async fn broadcast(bot: Bot, admin: ChatId, text: String) {
+    let chats = match DATABASE.get_receiving_chats().await {
+        Ok(chats) => chats,
+        Err(e) => {
+            log::error!("Failed to get the chats of the broadcast: {e}");
+            return;
+        }
+    };
+
+    let (mut sent, mut failed) = (0, 0);
+    for chat_id in chats {
+        match send::to_chat(chat_id, |chat_id| bot.send_message(chat_id, text.as_str()).send(), migrate_chat).await {
+            Ok(_) => sent += 1,
+            Err(e) => {
+                log::warn!("Failed to broadcast to {chat_id}: {e}");
+                record_send_failure(chat_id, &e).await;
+                failed += 1;
+            }
+        }
+        tokio::time::sleep(BROADCAST_INTERVAL).await;
+    }
+
+    log::info!("Broadcast of {admin} sent to {sent} chats, {failed} failed");
+    if let Err(e) = bot.send_message(admin, format!("Broadcast sent to {sent} chats, {failed} failed")).await {
+        log::error!("Failed to report the broadcast to {admin}: {e}");
+    }
+}
+
+/// Handler for callback query, the errors are reported to the user
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `cbq` - The callback query received
+///
+/// # Return
+/// Return Ok, the errors are already reported
+async fn callback_handler(
+    bot: Bot,
+    cbq: CallbackQuery,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let chat_id = cbq.message.as_ref().map(|message| message.chat.id);
+    let mut lang = Language::default();
+    if let Some(message) = &cbq.message {
+        refresh_user(&message.chat).await;
+        lang = chat_language(message.chat.id, Some(&cbq.from)).await;
+    }
+    if let Err(e) = handle_callback(bot.clone(), cbq, lang).await {
+        if let Some(chat_id) = chat_id {
+            report_error(bot, chat_id, lang, e).await;
+        }
+    }
     Ok(())
 }
+
+/// This function handle a callback query and answer it
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `cbq` - The callback query received
+/// * `lang` - The language of the chat
+///
+/// # Return
+/// Return Ok if no error
+async fn handle_callback(
+    bot: Bot,
+    cbq: CallbackQuery,
+    lang: Language,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (data, Some(Message { id, chat, .. })) = (cbq.data.as_deref().unwrap_or_default(), &cbq.message) else {
+        log::info!("Callback query from {:?} with data {:?}", cbq.from, cbq.data);
+        bot.answer_callback_query(&cbq.id).await?;
+        return Ok(());
+    };
+
+    let result = match CallbackAction::parse(data) {
+        Ok(action) => handle_action(bot.clone(), chat.id, *id, &cbq.from, action, lang).await,
+        Err(e) => {
+            log::warn!("Callback query from {} rejected: {e}", chat.id);
+            Ok(Some(EXPIRED_BUTTON))
+        }
+    };
+
+    // The query is always answered, with an alert if the button can't be used
+    let answer = bot.answer_callback_query(&cbq.id);
+    match result {
+        Ok(Some(alert)) => answer.text(lang.text(alert)).show_alert(true).await?,
+        _ => answer.await?,
+    };
+    result.map(|_| ())
+}
+
+/// This function do the action of a button
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `chat_id` - The chat of the button
+/// * `id` - The message of the button
+/// * `from` - The user who used the button, a member in a group
+/// * `action` - The action read from the button
+/// * `lang` - The language of the chat
+///
+/// # Return
+/// Return the key of the alert to show if the action can't be done, None if it is done
+async fn handle_action(
+    bot: Bot,
+    chat_id: ChatId,
+    id: MessageId,
+    from: &teloxide::types::User,
+    action: CallbackAction,
+    lang: Language,
+) -> Result<Option<&'static str>, Box<dyn Error + Send + Sync>> {
+    // In a group, the settings are changed by the administrators
+    let setting = matches!(
+        action,
+        CallbackAction::Timezone(_)
+            | CallbackAction::Import(_)
+            | CallbackAction::DeleteTracker { .. }
+            | CallbackAction::DeleteAccount { .. }
+            | CallbackAction::Language(_)
+    );
+    if setting && !is_member_admin(&bot, chat_id, from.id).await? {
+        return Ok(Some(ADMIN_ONLY));
+    }
+    // The buttons of a broadcast are only used by the administrators of the bot
+    if matches!(action, CallbackAction::Broadcast { .. }) && !is_bot_admin(chat_id) {
+        return Ok(Some(EXPIRED_BUTTON));
+    }
+
+    match action {
+        CallbackAction::Edit { rank_day } => {
+            /********
+             * EDIT *
+             ********/
+
+            let mut rank_day = match get_chat_rank_day(chat_id, rank_day).await? {
+                Some(rank_day) => rank_day,
+                None => return Ok(Some(DAY_NOT_FOUND)),
+            };
+
+            // Clear rank in rank day list
+            rank_day.set_rank(None);
+            rank_day.set_rated_at(None);
+            rank_day.set_id_msg(id);
+            DATABASE.update_rank(rank_day.clone()).await?;
+
+            // If edit, send message with rank day list
+            send_day_rank_message(bot.clone(), &rank_day, Some(id)).await?;
+        }
+        CallbackAction::Comment { rank_day } => {
+            /***********
+             * COMMENT *
+             ***********/
+
+            // The day of a group belongs to every member, only a private day is commented
+            if !chat_id.is_user() {
+                return Ok(Some(COMMENT_PRIVATE_ONLY));
+            }
+            let rank_day = match get_chat_rank_day(chat_id, rank_day).await? {
+                Some(rank_day) => rank_day,
+                None => return Ok(Some(DAY_NOT_FOUND)),
+            };
+
+            // Wait the comment as next text message of the user for this rank day
+            PENDING_COMMENTS.lock().unwrap().insert((chat_id, from.id), rank_day.get_id());
+
+            let mut message = lang.text("comment-send");
+            if let Some(comment) = rank_day.get_comment() {
+                message.push_str(format!("\n{}", lang.format("comment-actual", &[("comment", &comment)])).as_str());
+            }
+            message.push_str(format!("\n{}", lang.text("comment-cancel")).as_str());
+            bot.send_message(chat_id, message).await?;
+        }
+        CallbackAction::DeleteComment { rank_day } => {
+            /******************
+             * DELETE COMMENT *
+             ******************/
+
+            if !chat_id.is_user() {
+                return Ok(Some(COMMENT_PRIVATE_ONLY));
+            }
+            if get_chat_rank_day(chat_id, rank_day).await?.is_none() {
+                return Ok(Some(DAY_NOT_FOUND));
+            }
+            save_comment(bot.clone(), chat_id, lang, rank_day, None).await?;
+        }
+        CallbackAction::Timezone(timezone) => {
+            /************
+             * TIMEZONE *
+             ************/
+
+            let message = match DATABASE.set_timezone(chat_id, timezone).await {
+                Ok(_) => lang.format("timezone-done", &[("timezone", &timezone.name())]),
+                Err(DbError::UserNotFound(_)) => lang.text("start-first"),
+                Err(e) => return Err(e.into()),
+            };
+            SCHEDULE_CHANGED.notify_one();
+            bot.edit_message_text(chat_id, id, message).await?;
+        }
+        CallbackAction::Rate { tracker, date } => {
+            /********
+             * RATE *
+             ********/
+
+            let tracker = DATABASE.get_tracker_by_id(tracker).await?;
+            match tracker.filter(|tracker| tracker.get_user().get_chat_id() == chat_id) {
+                Some(tracker) => rate_day(bot.clone(), tracker, date).await?,
+                None => return Ok(Some(TRACKER_NOT_FOUND)),
+            }
+        }
+        CallbackAction::Import(choice) => {
+            /**********
+             * IMPORT *
+             **********/
+
+            let pending = PENDING_IMPORTS.lock().unwrap().remove(&chat_id);
+            let tracker = match &pending {
+                Some((tracker_id, _)) => DATABASE.get_tracker_by_id(*tracker_id).await?,
+                None => None,
+            };
+            let message = match (pending, tracker) {
+                (Some(_), _) if choice == ImportChoice::Cancel => lang.text("cancel-import"),
+                (Some((_, rows)), Some(tracker)) => {
+                    let saved = DATABASE.import_rank_days(&tracker, &rows, choice == ImportChoice::Replace).await?;
+                    lang.format("import-done", &[("days", &saved), ("tracker", &tracker_suffix(&tracker))])
+                }
+                _ => lang.text("import-over"),
+            };
+            bot.edit_message_text(chat_id, id, message).await?;
+        }
+        CallbackAction::DeleteTracker { tracker } => {
+            /******************
+             * DELETE TRACKER *
+             ******************/
+
+            let message = match tracker {
+                Some(tracker_id) => {
+                    let tracker = DATABASE.get_tracker_by_id(tracker_id).await?;
+                    match tracker.filter(|tracker| tracker.get_user().get_chat_id() == chat_id) {
+                        Some(tracker) => {
+                            DATABASE.delete_tracker(tracker.get_id()).await?;
+                            SCHEDULE_CHANGED.notify_one();
+                            lang.format("deletetracker-done", &[("name", &tracker.get_name())])
+                        }
+                        None => lang.text(TRACKER_NOT_FOUND),
+                    }
+                }
+                None => lang.text("delete-cancelled"),
+            };
+            bot.edit_message_text(chat_id, id, message).await?;
+        }
+        CallbackAction::DeleteAccount { confirm } => {
+            /******************
+             * DELETE ACCOUNT *
+             ******************/
+
+            let message = match confirm {
+                true => {
+                    DATABASE.delete_user(chat_id).await?;
+                    PENDING_COMMENTS.lock().unwrap().retain(|(chat, _), _| *chat != chat_id);
+                    IMPORT_TRACKERS.lock().unwrap().remove(&chat_id);
+                    PENDING_IMPORTS.lock().unwrap().remove(&chat_id);
+                    SCHEDULE_CHANGED.notify_one();
+                    lang.text("deleteme-done")
+                }
+                false => lang.text("delete-cancelled"),
+            };
+            bot.edit_message_text(chat_id, id, message).await?;
+        }
+        CallbackAction::Broadcast { confirm } => {
+            /*************
+             * BROADCAST *
+             *************/
+
+            let pending = PENDING_BROADCASTS.lock().unwrap().remove(&chat_id);
+            let message = match (pending, confirm) {
+                (Some(text), true) => {
+                    tokio::spawn(broadcast(bot.clone(), chat_id, text));
+                    "Broadcast started, you will receive its result"
+                }
+                (Some(_), false) => "Broadcast cancelled",
+                (None, _) => "This broadcast is over, send /broadcast again",
+            };
+            bot.edit_message_text(chat_id, id, message).await?;
+        }
+        CallbackAction::Language(language) => {
+            /************
+             * LANGUAGE *
+             ************/
+
+            DATABASE.set_language(chat_id, language).await?;
+            bot.edit_message_text(chat_id, id, language.text("language-done")).await?;
+        }
+        CallbackAction::Rank { rank_day, rank } => {
+            /********
+             * RANK *
+             ********/
+
+            let mut rank_day = match get_chat_rank_day(chat_id, rank_day).await? {
+                Some(rank_day) => rank_day,
+                None => return Ok(Some(DAY_NOT_FOUND)),
+            };
+            rank_day.set_id_msg(id);
+
+            if !rank_day.get_tracker().get_scale().contains(rank) {
+                // The keyboard is older than the scale, show the actual one
+                bot.send_message(chat_id, lang.text("rank-not-in-scale")).await?;
+                send_day_rank_message(bot.clone(), &rank_day, Some(id)).await?;
+                return Ok(None);
+            }
+
+            // In a group, the rank is the one of the member and the message stays for the others
+            if !chat_id.is_user() {
+                DATABASE.set_group_rank(rank_day.get_id(), from.id, &from.full_name(), rank).await?;
+                METRICS.add_rating(rank);
+                send_day_rank_message(bot.clone(), &rank_day, Some(id)).await?;
+                return Ok(None);
+            }
+
+            // Update rank in rank day list
+            rank_day.set_rank(Option::from(rank));
+            rank_day.set_rated_at(Option::from(Utc::now()));
+            DATABASE.update_rank(rank_day.clone()).await?;
+            METRICS.add_rating(rank);
+
+            // Send message with rank
+            send_day_message(bot.clone(), &rank_day).await?;
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Upper bounds (in seconds) of the buckets of the durations of the database requests
+const DB_BUCKETS: [f64; 12] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+lazy_static! {
+    /// Metrics of the bot since it started, exposed in the Prometheus format
+    pub static ref METRICS: Metrics = Metrics::default();
+}
+
+/// Durations of the requests of a database operation
+#[derive(Default)]
+struct Histogram {
+    /// Number of requests by bucket of `DB_BUCKETS`, not cumulative
+    buckets: [u64; DB_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = DB_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// Counters of the activity of the bot
+#[derive(Default)]
+pub struct Metrics {
+    updates_: AtomicU64,
+    prompts_: AtomicU64,
+    send_failures_: AtomicU64,
+    /// Number of ratings by rank
+    ratings_: Mutex<BTreeMap<u8, u64>>,
+    /// Durations of the requests by operation of the database
+    db_durations_: Mutex<BTreeMap<&'static str, Histogram>>,
+    /// Timestamp of the last loop of the scheduler, 0 before the first one
+    scheduler_tick_: AtomicI64,
+}
+
+impl Metrics {
+    /// Count an update received from Telegram
+    pub fn add_update(&self) {
+        self.updates_.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count the message of a day sent by a reminder
+    pub fn add_prompt(&self) {
+        self.prompts_.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a message which couldn't be sent, temporary or not
+    pub fn add_send_failure(&self) {
+        self.send_failures_.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a day ranked (or a rank of a member of a group)
+    pub fn add_rating(&self, rank: u8) {
+        *self.ratings_.lock().unwrap().entry(rank).or_default() += 1;
+    }
+
+    /// Save the duration of a request of the database
+    pub fn observe_db(&self, operation: &'static str, seconds: f64) {
+        self.db_durations_.lock().unwrap().entry(operation).or_default().observe(seconds);
+    }
+
+    /// Save the time of a loop of the scheduler
+    pub fn tick_scheduler(&self, time: DateTime<Utc>) {
+        self.scheduler_tick_.store(time.timestamp(), Ordering::Relaxed);
+    }
+
+    /// Time of the last loop of the scheduler, None if it didn't loop yet
+    pub fn get_scheduler_tick(&self) -> Option<DateTime<Utc>> {
+        match self.scheduler_tick_.load(Ordering::Relaxed) {
+            0 => None,
+            tst => DateTime::from_timestamp(tst, 0),
+        }
+    }
+
+    /// This function write the metrics in the text format of Prometheus
+    ///
+    /// # Return
+    /// Return the text of the metrics
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let updates = self.updates_.load(Ordering::Relaxed);
+        header(&mut text, "picole_updates_total", "counter", "Updates received from Telegram");
+        let _ = writeln!(text, "picole_updates_total {updates}");
+        let prompts = self.prompts_.load(Ordering::Relaxed);
+        header(&mut text, "picole_prompts_sent_total", "counter", "Messages of a day sent by the reminders");
+        let _ = writeln!(text, "picole_prompts_sent_total {prompts}");
+        let send_failures = self.send_failures_.load(Ordering::Relaxed);
+        header(&mut text, "picole_send_failures_total", "counter", "Messages which couldn't be sent");
+        let _ = writeln!(text, "picole_send_failures_total {send_failures}");
+
+        header(&mut text, "picole_ratings_total", "counter", "Days ranked, by rank");
+        for (rank, count) in self.ratings_.lock().unwrap().iter() {
+            let _ = writeln!(text, "picole_ratings_total{{rank=\"{rank}\"}} {count}");
+        }
+
+        let tick = self.scheduler_tick_.load(Ordering::Relaxed);
+        header(&mut text, "picole_scheduler_tick_timestamp_seconds", "gauge", "Time of the last loop of the scheduler");
+        let _ = writeln!(text, "picole_scheduler_tick_timestamp_seconds {tick}");
+
+        let name = "picole_db_duration_seconds";
+        header(&mut text, name, "histogram", "Durations of the requests of the database, by operation");
+        for (operation, histogram) in self.db_durations_.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in DB_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(text, "{name}_bucket{{operation=\"{operation}\",le=\"{bound}\"}} {cumulative}");
+            }
+            let _ = writeln!(text, "{name}_bucket{{operation=\"{operation}\",le=\"+Inf\"}} {}", histogram.count);
+            let _ = writeln!(text, "{name}_sum{{operation=\"{operation}\"}} {}", histogram.sum);
+            let _ = writeln!(text, "{name}_count{{operation=\"{operation}\"}} {}", histogram.count);
+        }
+        text
+    }
+}
+
+/// Write the description and the type of a metric, before its values
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Measure the duration of a database operation, it is saved when the timer is dropped
+/// so the operations returning early (ex: with an error) are measured too
+pub struct DbTimer {
+    operation: &'static str,
+    start: Instant,
+}
+
+impl DbTimer {
+    pub fn start(operation: &'static str) -> DbTimer {
+        DbTimer { operation, start: Instant::now() }
+    }
+}
+
+impl Drop for DbTimer {
+    fn drop(&mut self) {
+        METRICS.observe_db(self.operation, self.start.elapsed().as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values of the lines of a metric, with their labels
+    fn values<'a>(text: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+        text.lines()
+            .filter(|line| line.starts_with(name) && line[name.len()..].starts_with(['{', ' ']))
+            .filter_map(|line| line[name.len()..].rsplit_once(' '))
+            .collect()
+    }
+
+    #[test]
+    fn every_metric_has_help_and_type() {
+        let text = Metrics::default().render();
+        let metrics = [
+            ("picole_updates_total", "counter"),
+            ("picole_prompts_sent_total", "counter"),
+            ("picole_send_failures_total", "counter"),
+            ("picole_ratings_total", "counter"),
+            ("picole_scheduler_tick_timestamp_seconds", "gauge"),
+            ("picole_db_duration_seconds", "histogram"),
+        ];
+        let lines: Vec<&str> = text.lines().collect();
+        for (name, kind) in metrics {
+            let help = lines.iter().position(|line| line.starts_with(&format!("# HELP {name} "))).unwrap();
+            assert_eq!(lines[help + 1], format!("# TYPE {name} {kind}"));
+        }
+        assert_eq!(text.lines().filter(|line| line.starts_with("# TYPE ")).count(), metrics.len());
+    }
+
+    #[test]
+    fn counters_are_rendered() {
+        let metrics = Metrics::default();
+        metrics.add_update();
+        metrics.add_update();
+        metrics.add_prompt();
+        metrics.add_rating(4);
+        metrics.add_rating(0);
+        metrics.add_rating(4);
+        let text = metrics.render();
+        assert_eq!(values(&text, "picole_updates_total"), [("", "2")]);
+        assert_eq!(values(&text, "picole_prompts_sent_total"), [("", "1")]);
+        assert_eq!(values(&text, "picole_send_failures_total"), [("", "0")]);
+        assert_eq!(values(&text, "picole_ratings_total"), [("{rank=\"0\"}", "1"), ("{rank=\"4\"}", "2")]);
+        assert_eq!(values(&text, "picole_scheduler_tick_timestamp_seconds"), [("", "0")]);
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = Metrics::default();
+        for seconds in [0.0005, 0.003, 0.003, 0.2, 7.0] {
+            metrics.observe_db("get_user", seconds);
+        }
+        metrics.observe_db("ping", 0.001);
+        let text = metrics.render();
+
+        let name = "picole_db_duration_seconds";
+        let buckets: Vec<(&str, &str)> = values(&text, &format!("{name}_bucket"))
+            .into_iter()
+            .filter(|(labels, _)| labels.contains("operation=\"get_user\""))
+            .collect();
+        let expected = [
+            ("0.001", "1"),
+            ("0.0025", "1"),
+            ("0.005", "3"),
+            ("0.01", "3"),
+            ("0.025", "3"),
+            ("0.05", "3"),
+            ("0.1", "3"),
+            ("0.25", "4"),
+            ("0.5", "4"),
+            ("1", "4"),
+            ("2.5", "4"),
+            ("5", "4"),
+            ("+Inf", "5"),
+        ];
+        let expected: Vec<(String, &str)> = expected
+            .iter()
+            .map(|(bound, count)| (format!("{{operation=\"get_user\",le=\"{bound}\"}}"), *count))
+            .collect();
+        assert_eq!(buckets.iter().map(|(labels, count)| (labels.to_string(), *count)).collect::<Vec<_>>(), expected);
+
+        // The bucket +Inf counts every request
+        assert_eq!(values(&text, &format!("{name}_count")), [("{operation=\"get_user\"}", "5"), ("{operation=\"ping\"}", "1")]);
+        assert_eq!(values(&text, &format!("{name}_bucket")).last(), Some(&("{operation=\"ping\",le=\"+Inf\"}", "1")));
+        let sum: f64 = values(&text, &format!("{name}_sum"))[0].1.parse().unwrap();
+        assert!((sum - 7.2065).abs() < 1e-9);
+    }
+}
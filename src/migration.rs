@@ -0,0 +1,386 @@
+use crate::rank_day::DATE_FORMAT;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{Acquire, Executor, Row, SqliteConnection};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Step of a migration which can't be written in SQL
+type Step = for<'c> fn(&'c mut SqliteConnection) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'c>>;
+
+/// A numbered change of the database schema
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    sql: &'static str,
+    step: Option<Step>,
+}
+
+/// Every migration, in the order they are applied
+///
+/// A migration is never changed once released, a change of the schema is a new migration.
+/// The DEFAULTs of the settings in the migrations (timezone `UTC`, scale `0-5`, hour 22) only
+/// fill the rows existing when they run: the bot always saves the values of the configuration.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_tables",
+        sql: include_str!("migrations/0001_create_tables.sql"),
+        step: None,
+    },
+    Migration {
+        version: 2,
+        name: "user_timezone",
+        sql: include_str!("migrations/0002_user_timezone.sql"),
+        step: None,
+    },
+    Migration {
+        version: 3,
+        name: "user_minute_nudge",
+        sql: include_str!("migrations/0003_user_minute_nudge.sql"),
+        step: None,
+    },
+    Migration {
+        version: 4,
+        name: "user_last_prompt",
+        sql: include_str!("migrations/0004_user_last_prompt.sql"),
+        step: None,
+    },
+    Migration {
+        version: 5,
+        name: "rank_day_date",
+        sql: include_str!("migrations/0005_rank_day_date.sql"),
+        step: Some(fill_rank_day_date),
+    },
+    Migration {
+        version: 6,
+        name: "rank_day_unique_date",
+        sql: include_str!("migrations/0006_rank_day_unique_date.sql"),
+        step: None,
+    },
+    Migration {
+        version: 7,
+        name: "rank_day_rated_at",
+        sql: include_str!("migrations/0007_rank_day_rated_at.sql"),
+        step: None,
+    },
+    Migration {
+        version: 8,
+        name: "user_scale_question",
+        sql: include_str!("migrations/0008_user_scale_question.sql"),
+        step: None,
+    },
+    Migration {
+        version: 9,
+        name: "trackers",
+        sql: include_str!("migrations/0009_trackers.sql"),
+        step: None,
+    },
+    Migration {
+        version: 10,
+        name: "group_ranks",
+        sql: include_str!("migrations/0010_group_ranks.sql"),
+        step: None,
+    },
+    Migration {
+        version: 11,
+        name: "user_display_name",
+        sql: include_str!("migrations/0011_user_display_name.sql"),
+        step: None,
+    },
+    Migration {
+        version: 12,
+        name: "user_pause",
+        sql: include_str!("migrations/0012_user_pause.sql"),
+        step: None,
+    },
+    Migration {
+        version: 13,
+        name: "user_send_failure",
+        sql: include_str!("migrations/0013_user_send_failure.sql"),
+        step: None,
+    },
+    Migration {
+        version: 14,
+        name: "user_recaps",
+        sql: include_str!("migrations/0014_user_recaps.sql"),
+        step: None,
+    },
+    Migration {
+        version: 15,
+        name: "user_language",
+        sql: include_str!("migrations/0015_user_language.sql"),
+        step: None,
+    },
+];
+
+/// Table of the migrations applied
+const SCHEMA_VERSION: &str = "CREATE TABLE IF NOT EXISTS schema_version (\
+                    version INTEGER NOT NULL CONSTRAINT schema_version_pk PRIMARY KEY,\
+                    name TEXT NOT NULL,\
+                    applied_at INTEGER(8) NOT NULL)";
+
+/// This function apply the migrations not applied yet to the database
+///
+/// All the migrations are applied in a single transaction, so the database is never
+/// left half migrated.
+///
+/// # Arguments
+/// * `conn` - The connection to the database
+/// * `dry_run` - If true, the transaction is rolled back after the migrations
+///
+/// # Return
+/// Return the migrations applied (or which would be applied for a dry run)
+pub async fn run(conn: &mut SqliteConnection, dry_run: bool) -> Result<Vec<&'static Migration>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    tx.execute(SCHEMA_VERSION).await?;
+
+    let row = tx.fetch_one("SELECT COALESCE(MAX(version), 0) AS version FROM schema_version").await?;
+    let mut version: i64 = row.try_get("version")?;
+
+    // Database created before the migrations, its schema gives the migrations already done
+    if version == 0 {
+        version = legacy_version(&mut tx).await?;
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            record(&mut tx, migration).await?;
+        }
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > version).collect();
+    for migration in &pending {
+        log::info!("Apply migration {} {}", migration.version, migration.name);
+        tx.execute(migration.sql).await?;
+        if let Some(step) = migration.step {
+            step(&mut tx).await?;
+        }
+        record(&mut tx, migration).await?;
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(pending)
+}
+
+/// Save a migration as applied
+async fn record(conn: &mut SqliteConnection, migration: &Migration) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO schema_version (version, name, applied_at) VALUES (?, ?, ?)")
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(Utc::now().timestamp())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Find the last migration matching the schema of a database without schema_version
+async fn legacy_version(conn: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+    if !has_column(conn, "User", "id").await? {
+        return Ok(0);
+    }
+    let columns = [
+        (6, "Rank_day", "date"),
+        (4, "User", "last_prompt"),
+        (3, "User", "minute"),
+        (2, "User", "timezone"),
+    ];
+    for (version, table, column) in columns {
+        if has_column(conn, table, column).await? {
+            return Ok(version);
+        }
+    }
+    Ok(1)
+}
+
+async fn has_column(conn: &mut SqliteConnection, table: &str, column: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT name FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table)
+        .bind(column)
+        .fetch_optional(conn)
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Fill the local date of the rank days with the timezone of their user
+fn fill_rank_day_date(conn: &mut SqliteConnection) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + '_>> {
+    Box::pin(async move {
+        let rows = conn
+            .fetch_all("SELECT Rank_day.id, Rank_day.time, User.timezone
+                              FROM Rank_day
+                              join User on User.id = Rank_day.user_id")
+            .await?;
+        for row in rows {
+            let id: i64 = row.try_get("id")?;
+            let tst: i64 = row.try_get("time")?;
+            let timezone: String = row.try_get("timezone")?;
+            let timezone = timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+            let date = DateTime::from_timestamp(tst, 0)
+                .unwrap_or_default()
+                .with_timezone(&timezone)
+                .date_naive();
+            sqlx::query("UPDATE Rank_day SET date=? WHERE id=?")
+                .bind(date.format(DATE_FORMAT).to_string())
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Connection;
+
+    async fn connection() -> SqliteConnection {
+        SqliteConnection::connect("sqlite::memory:").await.unwrap()
+    }
+
+    /// Apply the migrations up to a version, recorded or as a database created before the migrations
+    async fn migrate_to(conn: &mut SqliteConnection, version: i64, recorded: bool) {
+        if recorded {
+            conn.execute(SCHEMA_VERSION).await.unwrap();
+        }
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            conn.execute(migration.sql).await.unwrap();
+            if let Some(step) = migration.step {
+                step(conn).await.unwrap();
+            }
+            if recorded {
+                record(conn, migration).await.unwrap();
+            }
+        }
+    }
+
+    async fn versions(conn: &mut SqliteConnection) -> Vec<i64> {
+        let rows = conn.fetch_all("SELECT version FROM schema_version ORDER BY version").await.unwrap();
+        rows.iter().map(|row| row.get("version")).collect()
+    }
+
+    async fn tables(conn: &mut SqliteConnection) -> Vec<String> {
+        let rows = conn
+            .fetch_all("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .await
+            .unwrap();
+        rows.iter().map(|row| row.get("name")).collect()
+    }
+
+    fn all_versions() -> Vec<i64> {
+        MIGRATIONS.iter().map(|m| m.version).collect()
+    }
+
+    #[test]
+    fn migrations_are_numbered_in_order() {
+        let expected: Vec<i64> = (1..=MIGRATIONS.len() as i64).collect();
+        assert_eq!(all_versions(), expected);
+    }
+
+    #[tokio::test]
+    async fn fresh_install_applies_every_migration() {
+        let mut conn = connection().await;
+        let applied = run(&mut conn, false).await.unwrap();
+        assert_eq!(applied.iter().map(|m| m.version).collect::<Vec<_>>(), all_versions());
+        assert_eq!(versions(&mut conn).await, all_versions());
+        assert_eq!(tables(&mut conn).await, ["Group_rank", "Rank_day", "Tracker", "User", "schema_version"]);
+
+        // Nothing left to apply
+        assert!(run(&mut conn, false).await.unwrap().is_empty());
+        assert_eq!(versions(&mut conn).await, all_versions());
+    }
+
+    #[tokio::test]
+    async fn legacy_version_is_read_from_the_schema() {
+        let mut conn = connection().await;
+        assert_eq!(legacy_version(&mut conn).await.unwrap(), 0);
+        // The schemas released before the migrations: the date of the rank days came with its unique index
+        for version in [1, 2, 3, 4, 6] {
+            let mut conn = connection().await;
+            migrate_to(&mut conn, version, false).await;
+            assert_eq!(legacy_version(&mut conn).await.unwrap(), version);
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_database_gets_the_missing_migrations() {
+        let mut conn = connection().await;
+        migrate_to(&mut conn, 4, false).await;
+        conn.execute("INSERT INTO User (id, chat_id, username, timezone) VALUES (1, 5, 'alice', 'Pacific/Auckland');
+                      INSERT INTO Rank_day (user_id, time, rank) VALUES (1, 1709319600, 3)")
+            .await
+            .unwrap();
+
+        let applied = run(&mut conn, false).await.unwrap();
+        assert_eq!(applied.first().map(|m| m.version), Some(5));
+        assert_eq!(versions(&mut conn).await, all_versions());
+
+        // The date was filled in the timezone of the user, and the day moved to his tracker
+        let row = conn
+            .fetch_one("SELECT Rank_day.date, Rank_day.rated_at, Tracker.name
+                        FROM Rank_day JOIN Tracker ON Tracker.id = Rank_day.tracker_id")
+            .await
+            .unwrap();
+        assert_eq!(row.get::<String, _>("date"), "2024-03-02");
+        assert_eq!(row.get::<i64, _>("rated_at"), 1709319600);
+        assert_eq!(row.get::<String, _>("name"), "pixel");
+    }
+
+    #[tokio::test]
+    async fn dry_run_leaves_the_schema_unchanged() {
+        let mut conn = connection().await;
+        let applied = run(&mut conn, true).await.unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert!(tables(&mut conn).await.is_empty());
+
+        migrate_to(&mut conn, 10, true).await;
+        let applied = run(&mut conn, true).await.unwrap();
+        assert_eq!(applied.first().map(|m| m.version), Some(11));
+        assert_eq!(versions(&mut conn).await, (1..=10).collect::<Vec<_>>());
+        assert!(!has_column(&mut conn, "User", "display_name").await.unwrap());
+        assert!(has_column(&mut conn, "User", "id").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn user_rebuild_keeps_the_foreign_keys() {
+        let mut conn = connection().await;
+        migrate_to(&mut conn, 10, true).await;
+        conn.execute("INSERT INTO User (id, chat_id, username) VALUES (1, 5, 'alice'), (2, -7, 'the group');
+                      INSERT INTO Tracker (id, user_id, name, question) VALUES (1, 1, 'pixel', 'How'), (2, 2, 'pixel', 'How');
+                      INSERT INTO Rank_day (id, user_id, tracker_id, time, date, rank) VALUES (1, 1, 1, 0, '1970-01-01', 2), (2, 2, 2, 0, '1970-01-01', NULL);
+                      INSERT INTO Group_rank (rank_day_id, member_id, member_name, rank, rated_at) VALUES (2, 5, 'alice', 4, 0)")
+            .await
+            .unwrap();
+
+        run(&mut conn, false).await.unwrap();
+
+        assert!(conn.fetch_all("PRAGMA foreign_key_check").await.unwrap().is_empty());
+        for table in ["Tracker", "Rank_day"] {
+            let row = sqlx::query("SELECT 1 FROM pragma_foreign_key_list(?) WHERE \"table\" = 'User'")
+                .bind(table)
+                .fetch_optional(&mut conn)
+                .await
+                .unwrap();
+            assert!(row.is_some(), "{table}");
+        }
+        let rows = conn
+            .fetch_all("SELECT User.chat_id, User.telegram_id, User.username, User.display_name
+                        FROM Rank_day
+                        JOIN Tracker ON Tracker.id = Rank_day.tracker_id
+                        JOIN User ON User.id = Tracker.user_id AND User.id = Rank_day.user_id
+                        ORDER BY Rank_day.id")
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get::<Option<i64>, _>("telegram_id"), Some(5));
+        assert_eq!(rows[0].get::<Option<String>, _>("username").as_deref(), Some("alice"));
+        assert_eq!(rows[1].get::<Option<i64>, _>("telegram_id"), None);
+        assert_eq!(rows[1].get::<Option<String>, _>("username"), None);
+        assert_eq!(rows[1].get::<String, _>("display_name"), "the group");
+
+        // The rebuilt table is still referenced
+        assert!(conn.execute("DELETE FROM User WHERE id = 1").await.is_err());
+    }
+}
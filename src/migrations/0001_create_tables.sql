@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS User (
+    id INTEGER CONSTRAINT user_pk PRIMARY KEY AUTOINCREMENT,
+    chat_id INTEGER(8) NOT NULL CONSTRAINT user_chat_id UNIQUE,
+    username TEXT NOT NULL,
+    hour INTEGER(1) NOT NULL DEFAULT 22
+);
+
+CREATE TABLE IF NOT EXISTS Rank_day (
+    id INTEGER CONSTRAINT rank_day_pk PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL CONSTRAINT User_id_fk REFERENCES User (id),
+    time INTEGER(8) NOT NULL,
+    id_msg INTEGER(4),
+    rank INTEGER(1),
+    comment TEXT
+);
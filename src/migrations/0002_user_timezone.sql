@@ -0,0 +1 @@
+ALTER TABLE User ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
@@ -0,0 +1,3 @@
+ALTER TABLE User ADD COLUMN minute INTEGER(1) NOT NULL DEFAULT 0;
+ALTER TABLE User ADD COLUMN nudge_hour INTEGER(1);
+ALTER TABLE User ADD COLUMN nudge_minute INTEGER(1);
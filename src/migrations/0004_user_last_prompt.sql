@@ -0,0 +1,2 @@
+ALTER TABLE User ADD COLUMN last_prompt INTEGER(8);
+ALTER TABLE User ADD COLUMN last_nudge INTEGER(8);
@@ -0,0 +1,2 @@
+-- The local date of each existing rank day is filled from the timezone of its user
+ALTER TABLE Rank_day ADD COLUMN date TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,12 @@
+-- Keep only the last ranked rank day of each user by date
+DELETE FROM Rank_day WHERE id NOT IN (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (
+            PARTITION BY user_id, date
+            ORDER BY rank IS NULL, time DESC, id DESC
+        ) AS n
+        FROM Rank_day
+    ) WHERE n = 1
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS rank_day_user_date ON Rank_day (user_id, date);
@@ -0,0 +1,4 @@
+-- Time the rank was given, the ranks given before are dated by their reminder
+ALTER TABLE Rank_day ADD COLUMN rated_at INTEGER(8);
+
+UPDATE Rank_day SET rated_at = time WHERE rank IS NOT NULL;
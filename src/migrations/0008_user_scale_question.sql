@@ -0,0 +1,2 @@
+ALTER TABLE User ADD COLUMN scale TEXT NOT NULL DEFAULT '0-5';
+ALTER TABLE User ADD COLUMN question TEXT NOT NULL DEFAULT 'How drunk are you';
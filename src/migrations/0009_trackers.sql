@@ -0,0 +1,34 @@
+-- The reminder, the scale and the question of a user move to his first tracker
+CREATE TABLE IF NOT EXISTS Tracker (
+    id INTEGER CONSTRAINT tracker_pk PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL CONSTRAINT Tracker_user_id_fk REFERENCES User (id),
+    name TEXT NOT NULL,
+    question TEXT NOT NULL,
+    scale TEXT NOT NULL DEFAULT '0-5',
+    hour INTEGER(1) NOT NULL DEFAULT 22,
+    minute INTEGER(1) NOT NULL DEFAULT 0,
+    nudge_hour INTEGER(1),
+    nudge_minute INTEGER(1),
+    last_prompt INTEGER(8),
+    last_nudge INTEGER(8),
+    CONSTRAINT tracker_user_name UNIQUE (user_id, name)
+);
+
+INSERT INTO Tracker (user_id, name, question, scale, hour, minute, nudge_hour, nudge_minute, last_prompt, last_nudge)
+SELECT id, 'pixel', question, scale, hour, minute, nudge_hour, nudge_minute, last_prompt, last_nudge FROM User;
+
+ALTER TABLE Rank_day ADD COLUMN tracker_id INTEGER CONSTRAINT Rank_day_tracker_id_fk REFERENCES Tracker (id);
+
+UPDATE Rank_day SET tracker_id = (SELECT Tracker.id FROM Tracker WHERE Tracker.user_id = Rank_day.user_id);
+
+DROP INDEX IF EXISTS rank_day_user_date;
+CREATE UNIQUE INDEX IF NOT EXISTS rank_day_tracker_date ON Rank_day (tracker_id, date);
+
+ALTER TABLE User DROP COLUMN hour;
+ALTER TABLE User DROP COLUMN minute;
+ALTER TABLE User DROP COLUMN nudge_hour;
+ALTER TABLE User DROP COLUMN nudge_minute;
+ALTER TABLE User DROP COLUMN last_prompt;
+ALTER TABLE User DROP COLUMN last_nudge;
+ALTER TABLE User DROP COLUMN scale;
+ALTER TABLE User DROP COLUMN question;
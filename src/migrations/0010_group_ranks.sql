@@ -0,0 +1,11 @@
+-- In a group chat, the rank days belong to the group and each member ranks his own day
+CREATE TABLE IF NOT EXISTS Group_rank (
+    id INTEGER CONSTRAINT group_rank_pk PRIMARY KEY AUTOINCREMENT,
+    rank_day_id INTEGER NOT NULL CONSTRAINT Group_rank_rank_day_id_fk REFERENCES Rank_day (id),
+    member_id INTEGER(8) NOT NULL,
+    member_name TEXT NOT NULL,
+    rank INTEGER(1) NOT NULL,
+    rated_at INTEGER(8) NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS group_rank_rank_day_member ON Group_rank (rank_day_id, member_id);
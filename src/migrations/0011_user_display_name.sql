@@ -0,0 +1,29 @@
+-- A user may have no username: he is registered by his Telegram id and shown with his display name
+-- (his username, else his first and last name, or the title of a group).
+-- The table is rebuilt to make the username optional, the foreign keys are checked at the commit.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE User_copy AS SELECT * FROM User;
+
+DROP TABLE User;
+
+CREATE TABLE User (
+    id INTEGER CONSTRAINT user_pk PRIMARY KEY AUTOINCREMENT,
+    chat_id INTEGER(8) NOT NULL CONSTRAINT user_chat_id UNIQUE,
+    telegram_id INTEGER(8) CONSTRAINT user_telegram_id UNIQUE,
+    username TEXT,
+    display_name TEXT NOT NULL,
+    timezone TEXT NOT NULL DEFAULT 'UTC'
+);
+
+-- The chat of a user is his Telegram id, the chats of the groups are negative
+INSERT INTO User (id, chat_id, telegram_id, username, display_name, timezone)
+SELECT id,
+       chat_id,
+       CASE WHEN chat_id > 0 THEN chat_id END,
+       CASE WHEN chat_id > 0 THEN username END,
+       username,
+       timezone
+FROM User_copy;
+
+DROP TABLE User_copy;
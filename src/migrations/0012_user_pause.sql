@@ -0,0 +1,3 @@
+-- A user can pause his reminders until a date, or stop them and keep his days
+ALTER TABLE User ADD COLUMN paused_until TEXT;
+ALTER TABLE User ADD COLUMN stopped INTEGER(1) NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- The last message which can't be sent to a user anymore (ex: he blocked the bot)
+ALTER TABLE User ADD COLUMN send_failure TEXT;
+ALTER TABLE User ADD COLUMN send_failed_at INTEGER(8);
@@ -0,0 +1,3 @@
+-- A user can receive a recap of his week on Sunday and of his month on the 1st
+ALTER TABLE User ADD COLUMN recap_weekly INTEGER(1) NOT NULL DEFAULT 0;
+ALTER TABLE User ADD COLUMN recap_monthly INTEGER(1) NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Language of the messages of a user, initialised from his Telegram language
+ALTER TABLE User ADD COLUMN language TEXT NOT NULL DEFAULT 'en';
@@ -0,0 +1,121 @@
+use crate::db::Database;
+use crate::metrics::{Metrics, METRICS};
+use chrono::Utc;
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::convert::Infallible;
+use std::error::Error;
+use std::net::SocketAddr;
+
+/// Longest time (in minutes) between two loops of the scheduler, it wakes up at least every hour
+const SCHEDULER_TIMEOUT_MINUTES: i64 = 65;
+
+/// Content type of the text format of Prometheus
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// This function start the server of `/healthz` and `/metrics`
+///
+/// # Arguments
+/// * `address` - The address listened
+/// * `database` - The database checked by `/healthz`
+///
+/// # Return
+/// Return an error if the address can't be listened, the server then runs in its own task
+pub fn serve(address: SocketAddr, database: &'static Database) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |request| handle_request(database, &METRICS, request)))
+    });
+    let server = Server::try_bind(&address)?.serve(make_service);
+    log::info!("Serve /healthz and /metrics on {address}");
+
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            log::error!("Monitoring server failed: {e}");
+        }
+    });
+    Ok(())
+}
+
+/// This function answer a request of the monitoring
+///
+/// # Arguments
+/// * `database` - The database checked by `/healthz`
+/// * `metrics` - The metrics served, with the last loop of the scheduler checked by `/healthz`
+/// * `request` - The request received
+///
+/// # Return
+/// Return the response: the metrics, or the health with 503 if the bot isn't healthy
+async fn handle_request(database: &Database, metrics: &Metrics, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let (status, content_type, body) = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/healthz") => match check_health(database, metrics).await {
+            Ok(()) => (StatusCode::OK, "text/plain", "ok\n".to_string()),
+            Err(problem) => {
+                log::warn!("Health check failed: {problem}");
+                (StatusCode::SERVICE_UNAVAILABLE, "text/plain", format!("{problem}\n"))
+            }
+        },
+        (&Method::GET, "/metrics") => (StatusCode::OK, METRICS_CONTENT_TYPE, metrics.render()),
+        _ => (StatusCode::NOT_FOUND, "text/plain", "not found\n".to_string()),
+    };
+
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert(CONTENT_TYPE, content_type.parse().unwrap());
+    Ok(response)
+}
+
+/// Check the database answers and the scheduler still sends the reminders
+///
+/// # Return
+/// Return the problem found if the bot isn't healthy
+async fn check_health(database: &Database, metrics: &Metrics) -> Result<(), String> {
+    database.ping().await.map_err(|e| format!("database unreachable: {e}"))?;
+    match metrics.get_scheduler_tick() {
+        Some(tick) if Utc::now() - tick <= chrono::Duration::minutes(SCHEDULER_TIMEOUT_MINUTES) => Ok(()),
+        Some(tick) => Err(format!("scheduler stalled since {}", tick.format("%Y-%m-%d %H:%M:%S UTC"))),
+        None => Err("scheduler not started".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database() -> Database {
+        let path = std::env::temp_dir().join(format!("picole-healthz-{}.db", std::process::id()));
+        Database::new(path.to_str().unwrap().to_string())
+    }
+
+    async fn get(metrics: &Metrics, path: &str) -> (StatusCode, String) {
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        let response = handle_request(&database(), metrics, request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn healthz_needs_a_recent_scheduler_loop() {
+        let metrics = Metrics::default();
+        assert_eq!(get(&metrics, "/healthz").await, (StatusCode::SERVICE_UNAVAILABLE, "scheduler not started\n".to_string()));
+
+        metrics.tick_scheduler(Utc::now() - chrono::Duration::minutes(SCHEDULER_TIMEOUT_MINUTES + 1));
+        let (status, body) = get(&metrics, "/healthz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.starts_with("scheduler stalled since "), "{body}");
+
+        metrics.tick_scheduler(Utc::now() - chrono::Duration::minutes(SCHEDULER_TIMEOUT_MINUTES - 1));
+        assert_eq!(get(&metrics, "/healthz").await, (StatusCode::OK, "ok\n".to_string()));
+    }
+
+    #[tokio::test]
+    async fn metrics_and_unknown_paths() {
+        let metrics = Metrics::default();
+        metrics.add_update();
+        let (status, body) = get(&metrics, "/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("\npicole_updates_total 1\n"), "{body}");
+        assert_eq!(get(&metrics, "/other").await.0, StatusCode::NOT_FOUND);
+    }
+}
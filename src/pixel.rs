@@ -0,0 +1,445 @@
+use crate::scale::Scale;
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+
+/// Colors of the ranks, from the worst to the best, spread on the scale of the user
+pub const RANK_COLORS: [[u8; 3]; 6] = [
+    [255, 255, 204],
+    [255, 237, 160],
+    [254, 178, 76],
+    [253, 141, 60],
+    [240, 59, 32],
+    [189, 0, 38],
+];
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const EMPTY: [u8; 3] = [230, 230, 230];
+const TEXT: [u8; 3] = [40, 40, 40];
+
+const CELL: u32 = 16;
+const GAP: u32 = 2;
+const MARGIN: u32 = 12;
+/// Smallest width of the stripe of a member in a cell of the group mosaic
+const STRIPE: u32 = 4;
+const LABEL_SCALE: u32 = 2;
+const TITLE_SCALE: u32 = 3;
+const MONTH_LETTERS: [char; 12] = ['J', 'F', 'M', 'A', 'M', 'J', 'J', 'A', 'S', 'O', 'N', 'D'];
+
+/// RGB image drawn in memory before PNG encoding
+struct Canvas {
+    width_: u32,
+    height_: u32,
+    data_: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Canvas {
+        let data = BACKGROUND
+            .iter()
+            .copied()
+            .cycle()
+            .take((width * height * 3) as usize)
+            .collect();
+        Canvas {
+            width_: width,
+            height_: height,
+            data_: data,
+        }
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+        for py in y..(y + height).min(self.height_) {
+            for px in x..(x + width).min(self.width_) {
+                let i = ((py * self.width_ + px) * 3) as usize;
+                self.data_[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Draw a text with the 3x5 bitmap font, each font pixel being `scale` pixels wide
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, scale: u32) {
+        for (n, c) in text.chars().enumerate() {
+            let glyph = glyph(c);
+            let gx = x + n as u32 * 4 * scale;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(gx + col * scale, y + row as u32 * scale, scale, scale, TEXT);
+                    }
+                }
+            }
+        }
+    }
+
+    fn encode(self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.width_, self.height_);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.data_)?;
+        }
+        Ok(bytes)
+    }
+}
+
+/// Width in pixels of a text drawn with `draw_text`
+fn text_width(text: &str, scale: u32) -> u32 {
+    (text.chars().count() as u32 * 4).saturating_sub(1) * scale
+}
+
+/// Keep the characters of a text which the font can draw (letters, digits and a few signs)
+fn printable(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || " -_.".contains(*c))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Rows of a 3x5 glyph, the most significant of the 3 bits is the left pixel
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        _ => [0; 5],
+    }
+}
+
+/// Index of the color of a rank in `RANK_COLORS`, a rank out of the scale has the color of the
+/// same rank on the scale 0-5
+pub fn rank_level(scale: &Scale, rank: u8) -> usize {
+    let last = RANK_COLORS.len() - 1;
+    match scale.position(rank) {
+        Some(position) => {
+            let size = scale.get_ranks().len().max(2) - 1;
+            (position * last + size / 2) / size
+        }
+        None => (rank as usize).min(last),
+    }
+}
+
+/// Color of a rank
+fn rank_color(scale: &Scale, rank: u8) -> [u8; 3] {
+    RANK_COLORS[rank_level(scale, rank)]
+}
+
+/// This function render the Picole Pixel of a year as a PNG image
+///
+/// The image has a column for each month and a row for each day of the month.
+/// Each day is colored with its rank, unrated days are left as empty cells.
+///
+/// # Arguments
+/// * `title` - The title written above the grid (ex: the username)
+/// * `year` - The year to render, days of other years are ignored
+/// * `days` - The rank of each rated day
+/// * `scale` - The scale of the ranks, for the colors and the legend
+///
+/// # Return
+/// Return the PNG encoded image
+pub fn render(title: &str, year: i32, days: &[(NaiveDate, u8)], scale: &Scale) -> Result<Vec<u8>, png::EncodingError> {
+    let ranks: HashMap<NaiveDate, u8> = days
+        .iter()
+        .filter(|(date, _)| date.year() == year)
+        .copied()
+        .collect();
+
+    draw(title, year, scale, &[], |date, _| ranks.get(&date).copied())
+}
+
+/// This function render the Picole Pixel of a year of the members of a group as a PNG image
+///
+/// Each day cell is split in a vertical stripe by member, in the order of the members,
+/// and the members are listed below the legend.
+///
+/// # Arguments
+/// * `title` - The title written above the grid (ex: the name of the group)
+/// * `year` - The year to render, days of other years are ignored
+/// * `members` - The name and the rank of each rated day of each member
+/// * `scale` - The scale of the ranks, for the colors and the legend
+///
+/// # Return
+/// Return the PNG encoded image
+pub fn render_group(
+    title: &str,
+    year: i32,
+    members: &[(String, Vec<(NaiveDate, u8)>)],
+    scale: &Scale,
+) -> Result<Vec<u8>, png::EncodingError> {
+    let ranks: Vec<HashMap<NaiveDate, u8>> = members
+        .iter()
+        .map(|(_, days)| days.iter().filter(|(date, _)| date.year() == year).copied().collect())
+        .collect();
+    let names: Vec<String> = members
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| match printable(name).as_str() {
+            "" => format!("{} member {}", i + 1, i + 1),
+            name => format!("{} {name}", i + 1),
+        })
+        .collect();
+
+    draw(title, year, scale, &names, |date, member| ranks.get(member)?.get(&date).copied())
+}
+
+/// This function draw the grid of a year, with a stripe by member in each cell
+///
+/// # Arguments
+/// * `title` - The title written above the grid, without the characters the font can't draw
+/// * `year` - The year to render
+/// * `scale` - The scale of the ranks, for the colors and the legend
+/// * `members` - The names of the members written below the legend, empty for a single user
+/// * `rank` - The rank of a date for a member (from 0), None if the day isn't rated
+fn draw(
+    title: &str,
+    year: i32,
+    scale: &Scale,
+    members: &[String],
+    rank: impl Fn(NaiveDate, usize) -> Option<u8>,
+) -> Result<Vec<u8>, png::EncodingError> {
+    let stripes = members.len().max(1) as u32;
+    let cell_width = CELL.max(stripes * STRIPE);
+    let title = match printable(title).as_str() {
+        "" => year.to_string(),
+        title => format!("{title} {year}"),
+    };
+    let label_width = text_width("31", LABEL_SCALE) + GAP * 2;
+    let grid_x = MARGIN + label_width;
+    let title_height = 5 * TITLE_SCALE + CELL;
+    let months_y = MARGIN + title_height;
+    let grid_y = months_y + 5 * LABEL_SCALE + GAP * 2;
+    let step = CELL + GAP;
+    let column_step = cell_width + GAP;
+    let legend_y = grid_y + 31 * step + CELL;
+
+    // Legend with the color of each rank, on several lines if it's wider than the grid
+    let right = grid_x + 12 * column_step;
+    let mut legend = Vec::new();
+    let (mut x, mut y) = (grid_x, legend_y);
+    for (rank, label) in scale.get_ranks().into_iter().zip(scale.get_labels()) {
+        // The font only has letters, digits and a few signs
+        let label = match label.chars().all(|c| c.is_ascii_alphanumeric() || " -_.".contains(c)) {
+            true => label,
+            false => rank.to_string(),
+        };
+        let entry_width = step + text_width(&label, LABEL_SCALE);
+        if x > grid_x && x + entry_width > right {
+            x = grid_x;
+            y += step;
+        }
+        legend.push((x, y, rank_color(scale, rank), label));
+        x += entry_width + GAP * 3;
+    }
+    let legend_right = legend
+        .iter()
+        .map(|(x, _, _, label)| x + step + text_width(label, LABEL_SCALE))
+        .max()
+        .unwrap_or(right);
+
+    // Members in the order of the stripes, one by line
+    let members_y = y + step + CELL;
+    let members_right = members
+        .iter()
+        .map(|name| grid_x + text_width(name, LABEL_SCALE))
+        .max()
+        .unwrap_or(right);
+    if !members.is_empty() {
+        y = members_y + (members.len() as u32 - 1) * step;
+    }
+
+    let width = (right.max(legend_right).max(members_right) + MARGIN).max(MARGIN * 2 + text_width(&title, TITLE_SCALE));
+    let height = y + CELL + MARGIN;
+    let mut canvas = Canvas::new(width, height);
+
+    canvas.draw_text(MARGIN, MARGIN, &title, TITLE_SCALE);
+
+    // Month initials above the columns
+    for (month, letter) in MONTH_LETTERS.iter().enumerate() {
+        let x = grid_x + month as u32 * column_step + (cell_width - text_width("M", LABEL_SCALE)) / 2;
+        canvas.draw_text(x, months_y, &letter.to_string(), LABEL_SCALE);
+    }
+
+    // Day numbers on the left of the rows
+    for day in 1..=31 {
+        let label = day.to_string();
+        let x = grid_x - GAP * 2 - text_width(&label, LABEL_SCALE);
+        let y = grid_y + (day - 1) * step + (CELL - 5 * LABEL_SCALE) / 2;
+        canvas.draw_text(x, y, &label, LABEL_SCALE);
+    }
+
+    // A cell for each existing day of the year
+    for month in 1..=12 {
+        for day in 1..=31 {
+            let date = match NaiveDate::from_ymd_opt(year, month, day) {
+                Some(date) => date,
+                None => continue,
+            };
+            let x = grid_x + (month - 1) * column_step;
+            let y = grid_y + (day - 1) * step;
+            for member in 0..stripes {
+                let color = match rank(date, member as usize) {
+                    Some(rank) => rank_color(scale, rank),
+                    None => EMPTY,
+                };
+                // The last stripe takes the pixels left by the division
+                let stripe_x = x + member * cell_width / stripes;
+                let stripe_width = x + (member + 1) * cell_width / stripes - stripe_x;
+                canvas.fill_rect(stripe_x, y, stripe_width, CELL, color);
+            }
+        }
+    }
+
+    for (x, y, color, label) in legend {
+        canvas.fill_rect(x, y, CELL, CELL, color);
+        canvas.draw_text(x + step, y + (CELL - 5 * LABEL_SCALE) / 2, &label, LABEL_SCALE);
+    }
+
+    for (i, name) in members.iter().enumerate() {
+        let y = members_y + i as u32 * step;
+        canvas.draw_text(grid_x, y + (CELL - 5 * LABEL_SCALE) / 2, name, LABEL_SCALE);
+    }
+
+    canvas.encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Position of the grid and distance between two cells, for the scale 0-5 of a user
+    const GRID_X: u32 = 30;
+    const GRID_Y: u32 = 57;
+    const STEP: u32 = CELL + GAP;
+
+    struct Image {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    }
+
+    impl Image {
+        fn decode(bytes: &[u8]) -> Image {
+            let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+            let mut data = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut data).unwrap();
+            assert_eq!(info.color_type, png::ColorType::Rgb);
+            data.truncate(info.buffer_size());
+            Image { width: info.width, height: info.height, data }
+        }
+
+        fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+            let i = ((y * self.width + x) * 3) as usize;
+            [self.data[i], self.data[i + 1], self.data[i + 2]]
+        }
+
+        /// Color of the center of the cell of a day
+        fn cell(&self, month: u32, day: u32) -> [u8; 3] {
+            self.pixel(GRID_X + (month - 1) * STEP + CELL / 2, GRID_Y + (day - 1) * STEP + CELL / 2)
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn image_has_a_column_by_month_and_a_row_by_day() {
+        let scale = Scale::parse("0-5").unwrap();
+        let image = Image::decode(&render("Ann", 2023, &[], &scale).unwrap());
+        assert_eq!((image.width, image.height), (258, 659));
+    }
+
+    #[test]
+    fn ranked_day_has_the_color_of_its_rank() {
+        let scale = Scale::parse("0-5").unwrap();
+        let days = [(date(2023, 3, 14), 5), (date(2023, 3, 15), 0), (date(2022, 3, 16), 5)];
+        let image = Image::decode(&render("Ann", 2023, &days, &scale).unwrap());
+        assert_eq!(image.cell(3, 14), RANK_COLORS[5]);
+        assert_eq!(image.cell(3, 15), RANK_COLORS[0]);
+        // Unranked day, and day of another year
+        assert_eq!(image.cell(3, 13), EMPTY);
+        assert_eq!(image.cell(3, 16), EMPTY);
+    }
+
+    #[test]
+    fn rank_color_is_spread_on_the_scale() {
+        let scale = Scale::parse("1-10").unwrap();
+        let days = [(date(2023, 1, 1), 1), (date(2023, 1, 2), 10)];
+        let image = Image::decode(&render("Ann", 2023, &days, &scale).unwrap());
+        assert_eq!(image.cell(1, 1), RANK_COLORS[0]);
+        assert_eq!(image.cell(1, 2), RANK_COLORS[5]);
+    }
+
+    #[test]
+    fn missing_days_have_no_cell() {
+        let scale = Scale::parse("0-5").unwrap();
+        let image = Image::decode(&render("Ann", 2023, &[], &scale).unwrap());
+        assert_eq!(image.cell(2, 28), EMPTY);
+        assert_eq!(image.cell(2, 29), BACKGROUND);
+        assert_eq!(image.cell(4, 31), BACKGROUND);
+        assert_eq!(image.cell(12, 31), EMPTY);
+    }
+
+    #[test]
+    fn leap_year_has_february_29() {
+        let scale = Scale::parse("0-5").unwrap();
+        let days = [(date(2024, 2, 29), 3)];
+        let image = Image::decode(&render("Ann", 2024, &days, &scale).unwrap());
+        assert_eq!(image.cell(2, 29), RANK_COLORS[rank_level(&scale, 3)]);
+        assert_eq!(image.cell(2, 30), BACKGROUND);
+        assert_eq!(image.cell(3, 1), EMPTY);
+    }
+
+    #[test]
+    fn title_keeps_only_the_characters_of_the_font() {
+        let scale = Scale::parse("0-5").unwrap();
+        let wine = "🍷".repeat(40);
+        let image = render(&format!("Zoë {wine} mood"), 2023, &[], &scale).unwrap();
+        assert_eq!(image, render("Zo  mood", 2023, &[], &scale).unwrap());
+        assert_eq!(Image::decode(&image).width, 258);
+        assert_eq!(render(&wine, 2023, &[], &scale).unwrap(), render("", 2023, &[], &scale).unwrap());
+
+        let members = [("Zoë 🍷".to_string(), vec![(date(2023, 1, 1), 2)])];
+        let image = render_group(&format!("Les fêtes {wine}"), 2023, &members, &scale).unwrap();
+        let members = [("Zo".to_string(), vec![(date(2023, 1, 1), 2)])];
+        assert_eq!(image, render_group("Les ftes", 2023, &members, &scale).unwrap());
+    }
+}
@@ -1,39 +1,100 @@
+use crate::tracker::Tracker;
 use crate::user::User;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use teloxide::types::MessageId;
 
+/// Format of the local date of a rank day, in the database, the exports and the buttons
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
 #[derive(Clone)]
 pub struct RankDay {
-    user_: User,
+    id_: i64,
+    tracker_: Tracker,
     time_: DateTime<Utc>,
+    date_: NaiveDate,
     id_msg_: MessageId,
     rank_: Option<u8>,
+    comment_: Option<String>,
+    rated_at_: Option<DateTime<Utc>>,
 }
 
 impl RankDay {
-    pub fn new(user: User, time: DateTime<Utc>, id_msg: MessageId) -> RankDay {
+    pub fn new(tracker: Tracker, time: DateTime<Utc>, id_msg: MessageId) -> RankDay {
+        // The evaluated day is the local date of the user
+        let date = time.with_timezone(&tracker.get_user().get_timezone()).date_naive();
         RankDay {
-            user_: user,
+            id_: 0,
+            tracker_: tracker,
             time_: time,
+            date_: date,
             id_msg_: id_msg,
             rank_: None,
+            comment_: None,
+            rated_at_: None,
         }
     }
 
+    /// The id of the rank day in the database, 0 if it isn't saved yet
+    pub fn get_id(&self) -> i64 {
+        self.id_
+    }
+
+    pub fn set_id(&mut self, id: i64) {
+        self.id_ = id;
+    }
+
+    pub fn set_id_msg(&mut self, id_msg: MessageId) {
+        self.id_msg_ = id_msg;
+    }
+
+    pub fn set_date(&mut self, date: NaiveDate) {
+        self.date_ = date;
+    }
+
+    pub fn set_rank(&mut self, rank: Option<u8>) {
+        self.rank_ = rank;
+    }
+
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.comment_ = comment;
+    }
+
+    pub fn set_rated_at(&mut self, rated_at: Option<DateTime<Utc>>) {
+        self.rated_at_ = rated_at;
+    }
+
     pub fn get_rank(&self) -> Option<u8> {
         self.rank_
     }
 
+    pub fn get_tracker(&self) -> Tracker {
+        self.tracker_.clone()
+    }
+
+    /// The user owning the tracker of the day
     pub fn get_user(&self) -> User {
-        self.user_.clone()
+        self.tracker_.get_user()
     }
 
     pub fn get_time(&self) -> DateTime<Utc> {
         self.time_
     }
 
+    pub fn get_date(&self) -> NaiveDate {
+        self.date_
+    }
+
     pub fn get_id_msg(&self) -> MessageId {
         self.id_msg_
     }
 
+    pub fn get_comment(&self) -> Option<String> {
+        self.comment_.clone()
+    }
+
+    /// Time the rank was given, None if the day isn't ranked
+    pub fn get_rated_at(&self) -> Option<DateTime<Utc>> {
+        self.rated_at_
+    }
+
 }
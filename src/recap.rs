@@ -0,0 +1,133 @@
+use crate::pixel::{rank_level, RANK_COLORS};
+use crate::scale::Scale;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Squares of the ranks from the worst to the best, one by color of the pixels (`RANK_COLORS`)
+///
+/// The emoji have a single square by color, so the lowest ranks are colder than their pixels.
+const RANK_SQUARES: [&str; RANK_COLORS.len()] = ["🟦", "🟩", "🟨", "🟧", "🟥", "🟫"];
+
+/// Square of a day not ranked
+const EMPTY_SQUARE: &str = "⬜";
+
+/// Number of squares by line
+const SQUARES_BY_LINE: usize = 7;
+
+/// Summary of the past period sent with the reminder
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Recap {
+    /// The week from Monday to Sunday, sent with the reminder of Sunday
+    Weekly,
+    /// The previous month, sent on the 1st
+    Monthly,
+}
+
+impl Recap {
+    /// Recaps due on a local date
+    ///
+    /// # Arguments
+    /// * `date` - The local date of the reminder
+    /// * `weekly` - True if the user wants the weekly recap
+    /// * `monthly` - True if the user wants the monthly recap
+    pub fn due_on(date: NaiveDate, weekly: bool, monthly: bool) -> Vec<Recap> {
+        let mut recaps = vec![];
+        if weekly && date.weekday() == Weekday::Sun {
+            recaps.push(Recap::Weekly);
+        }
+        if monthly && date.day() == 1 {
+            recaps.push(Recap::Monthly);
+        }
+        recaps
+    }
+
+    /// First and last date of the period summarized on a date
+    pub fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Recap::Weekly => (date - Duration::days(6), date),
+            Recap::Monthly => {
+                let last = date.with_day(1).unwrap_or(date) - Duration::days(1);
+                (last.with_day(1).unwrap_or(last), last)
+            }
+        }
+    }
+
+    /// First and last date of the period before the one summarized on a date
+    pub fn previous(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let (from, _) = self.bounds(date);
+        match self {
+            Recap::Weekly => (from - Duration::days(7), from - Duration::days(1)),
+            Recap::Monthly => Recap::Monthly.bounds(from),
+        }
+    }
+}
+
+/// This function draw the days of a period as emoji squares, a line by week
+///
+/// # Arguments
+/// * `days` - The ranks of the period sorted by date
+/// * `from` - The first date of the period
+/// * `to` - The last date of the period
+/// * `scale` - The scale of the tracker, which gives the color of the ranks
+pub fn squares(days: &[(NaiveDate, u8)], from: NaiveDate, to: NaiveDate, scale: &Scale) -> String {
+    let squares: Vec<&str> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| match days.iter().find(|(day, _)| *day == date) {
+            Some((_, rank)) => RANK_SQUARES[rank_level(scale, *rank)],
+            None => EMPTY_SQUARE,
+        })
+        .collect();
+    squares
+        .chunks(SQUARES_BY_LINE)
+        .map(|line| line.concat())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn weekly_recap_is_sent_on_sunday() {
+        // Sunday 5 May 2024, and Monday 6 May
+        assert_eq!(Recap::due_on(date(2024, 5, 5), true, true), vec![Recap::Weekly]);
+        assert_eq!(Recap::due_on(date(2024, 5, 6), true, true), vec![]);
+        assert_eq!(Recap::due_on(date(2024, 5, 5), false, true), vec![]);
+        // Sunday 1 September 2024
+        assert_eq!(Recap::due_on(date(2024, 9, 1), true, true), vec![Recap::Weekly, Recap::Monthly]);
+    }
+
+    #[test]
+    fn weekly_recap_covers_monday_to_sunday() {
+        assert_eq!(Recap::Weekly.bounds(date(2024, 5, 5)), (date(2024, 4, 29), date(2024, 5, 5)));
+        assert_eq!(Recap::Weekly.previous(date(2024, 5, 5)), (date(2024, 4, 22), date(2024, 4, 28)));
+        assert_eq!(Recap::Monthly.bounds(date(2024, 3, 1)), (date(2024, 2, 1), date(2024, 2, 29)));
+        assert_eq!(Recap::Monthly.previous(date(2024, 1, 1)), (date(2023, 11, 1), date(2023, 11, 30)));
+    }
+
+    #[test]
+    fn squares_are_distinct() {
+        for (i, square) in RANK_SQUARES.iter().enumerate() {
+            assert!(!RANK_SQUARES[i + 1..].contains(square), "{square}");
+            assert_ne!(*square, EMPTY_SQUARE);
+        }
+    }
+
+    #[test]
+    fn squares_follow_the_scale() {
+        let from = date(2024, 4, 29);
+        let scale = Scale::parse("0-5").unwrap();
+        let days: Vec<(NaiveDate, u8)> = (0..6).map(|rank| (from + Duration::days(rank as i64), rank)).collect();
+        assert_eq!(squares(&days, from, from + Duration::days(6), &scale), "🟦🟩🟨🟧🟥🟫⬜");
+
+        // The ranks of a scale of 1 to 10 are spread on the squares
+        let scale = Scale::parse("1-10").unwrap();
+        let days = [(from, 1), (from + Duration::days(1), 10)];
+        assert_eq!(squares(&days, from, from + Duration::days(8), &scale), "🟦🟫⬜⬜⬜⬜⬜\n⬜⬜");
+    }
+}
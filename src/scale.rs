@@ -0,0 +1,125 @@
+use std::fmt;
+
+/// Biggest number of ranks of a scale (the keyboard of a day must stay readable)
+pub const MAX_LABELS: usize = 10;
+
+/// Biggest number of characters of a custom label
+pub const MAX_LABEL_LENGTH: usize = 20;
+
+const EMOJI: [&str; 5] = ["😞", "🙁", "😐", "🙂", "😄"];
+
+/// Ranks proposed to a user
+///
+/// The rank saved for a day is `min` + the position of its label in the scale.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Scale {
+    min_: u8,
+    labels_: Vec<String>,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Scale::numeric(0, 5)
+    }
+}
+
+impl Scale {
+    fn numeric(min: u8, max: u8) -> Scale {
+        Scale {
+            min_: min,
+            labels_: (min..=max).map(|rank| rank.to_string()).collect(),
+        }
+    }
+
+    /// Parse a scale written by a user or saved in the database
+    ///
+    /// The scales are `0-5`, `1-10`, `emoji`, or custom labels separated by commas
+    /// (ex: `bad,ok,good`), saved as `custom:bad,ok,good`.
+    ///
+    /// # Return
+    /// Return None if the scale isn't valid
+    pub fn parse(text: &str) -> Option<Scale> {
+        let text = text.trim();
+        let scale = match text.to_lowercase().as_str() {
+            "0-5" => Scale::numeric(0, 5),
+            "1-10" => Scale::numeric(1, 10),
+            "emoji" => Scale {
+                min_: 0,
+                labels_: EMOJI.iter().map(|label| label.to_string()).collect(),
+            },
+            _ => {
+                let labels: Vec<String> = text
+                    .strip_prefix("custom:")
+                    .unwrap_or(text)
+                    .split(',')
+                    .map(|label| label.trim().to_string())
+                    .collect();
+                if labels.iter().any(|label| label.is_empty()) {
+                    return None;
+                }
+                Scale { min_: 0, labels_: labels }
+            }
+        };
+
+        // At least 2 distinct labels, short enough for a button
+        let mut labels = scale.labels_.clone();
+        labels.sort();
+        labels.dedup();
+        let short = labels.iter().all(|label| label.chars().count() <= MAX_LABEL_LENGTH);
+        match short && labels.len() == scale.labels_.len() && (2..=MAX_LABELS).contains(&labels.len()) {
+            true => Some(scale),
+            false => None,
+        }
+    }
+
+    /// Ranks of the scale, from the worst to the best
+    pub fn get_ranks(&self) -> Vec<u8> {
+        (0..self.labels_.len()).map(|i| self.min_ + i as u8).collect()
+    }
+
+    pub fn get_labels(&self) -> Vec<String> {
+        self.labels_.clone()
+    }
+
+    pub fn contains(&self, rank: u8) -> bool {
+        self.position(rank).is_some()
+    }
+
+    /// Position of a rank in the scale, from 0 for the worst
+    pub fn position(&self, rank: u8) -> Option<usize> {
+        let position = rank.checked_sub(self.min_)? as usize;
+        match position < self.labels_.len() {
+            true => Some(position),
+            false => None,
+        }
+    }
+
+    /// Label of a rank, the rank itself if it isn't in the scale (ex: the scale changed)
+    pub fn label(&self, rank: u8) -> String {
+        match self.position(rank) {
+            Some(position) => self.labels_[position].clone(),
+            None => rank.to_string(),
+        }
+    }
+
+    /// Find the rank of a label, or of a rank written as a number
+    pub fn parse_rank(&self, text: &str) -> Option<u8> {
+        let text = text.trim();
+        if let Some(position) = self.labels_.iter().position(|label| label == text) {
+            return Some(self.min_ + position as u8);
+        }
+        text.parse::<u8>().ok().filter(|rank| self.contains(*rank))
+    }
+}
+
+impl fmt::Display for Scale {
+    /// Write the scale as saved in the database
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in ["0-5", "1-10", "emoji"] {
+            if Scale::parse(name).as_ref() == Some(self) {
+                return write!(f, "{name}");
+            }
+        }
+        write!(f, "custom:{}", self.labels_.join(","))
+    }
+}
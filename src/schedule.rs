@@ -0,0 +1,148 @@
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Number of days of reminders sent at most after the bot was offline
+pub const MAX_CATCH_UP_DAYS: i64 = 7;
+
+/// This function compute the next time a daily reminder is due
+///
+/// The reminder is at `hour`:`minute` in the local time of `timezone`. If this local time
+/// doesn't exist (DST gap), the reminder is one hour later. If it exists twice (DST overlap),
+/// the first one is used.
+///
+/// # Arguments
+/// * `timezone` - The timezone of the user
+/// * `hour` - The local hour of the reminder
+/// * `minute` - The local minute of the reminder
+/// * `after` - The reminder returned is strictly after this time
+///
+/// # Return
+/// Return the time of the next reminder
+pub fn next_occurrence(timezone: Tz, hour: u8, minute: u8, after: DateTime<Utc>) -> DateTime<Utc> {
+    let mut date = after.with_timezone(&timezone).date_naive();
+    loop {
+        let utc = occurrence_on(timezone, hour, minute, date);
+        if utc > after {
+            return utc;
+        }
+        date = date.succ_opt().expect("Date out of range");
+    }
+}
+
+/// This function list the reminders due since the last one sent, from the oldest
+///
+/// After a long downtime, only the reminders of the last `MAX_CATCH_UP_DAYS` days are due.
+///
+/// # Arguments
+/// * `timezone` - The timezone of the user
+/// * `hour` - The local hour of the reminder
+/// * `minute` - The local minute of the reminder
+/// * `last` - The time of the last reminder sent
+/// * `now` - The actual time
+///
+/// # Return
+/// Return the times of the reminders to send, in chronological order
+pub fn due_occurrences(timezone: Tz, hour: u8, minute: u8, last: DateTime<Utc>, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let oldest = now - Duration::days(MAX_CATCH_UP_DAYS);
+    let mut due = next_occurrence(timezone, hour, minute, last.max(oldest));
+    let mut dues = Vec::new();
+    while due <= now {
+        dues.push(due);
+        due = next_occurrence(timezone, hour, minute, due);
+    }
+    dues
+}
+
+/// This function compute the time of a daily reminder on a given local date
+///
+/// # Arguments
+/// * `timezone` - The timezone of the user
+/// * `hour` - The local hour of the reminder
+/// * `minute` - The local minute of the reminder
+/// * `date` - The local date of the reminder
+///
+/// # Return
+/// Return the time of the reminder
+pub fn occurrence_on(timezone: Tz, hour: u8, minute: u8, date: NaiveDate) -> DateTime<Utc> {
+    let time = NaiveTime::from_hms_opt(hour as u32, minute as u32, 0).unwrap_or(NaiveTime::MIN);
+    to_utc(timezone, date.and_time(time)).expect("Local time not found")
+}
+
+/// Convert a local time to UTC, one hour later if it doesn't exist and the first if it exists twice
+fn to_utc(timezone: Tz, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let local = match timezone.from_local_datetime(&naive) {
+        LocalResult::Single(local) => Some(local),
+        LocalResult::Ambiguous(first, _) => Some(first),
+        LocalResult::None => timezone.from_local_datetime(&(naive + Duration::hours(1))).earliest(),
+    };
+    local.map(|local| local.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Zurich;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn occurrence_is_in_local_time() {
+        // UTC+1 in winter, UTC+2 in summer
+        assert_eq!(occurrence_on(Zurich, 22, 0, date(2024, 1, 15)), utc(2024, 1, 15, 21, 0));
+        assert_eq!(occurrence_on(Zurich, 22, 0, date(2024, 7, 15)), utc(2024, 7, 15, 20, 0));
+    }
+
+    #[test]
+    fn reminder_in_spring_gap_is_one_hour_later() {
+        // On 31 March 2024, 02:00 jumps to 03:00: 02:30 doesn't exist, the reminder is at 03:30 CEST
+        assert_eq!(occurrence_on(Zurich, 2, 30, date(2024, 3, 31)), utc(2024, 3, 31, 1, 30));
+        assert_eq!(next_occurrence(Zurich, 2, 30, utc(2024, 3, 30, 1, 30)), utc(2024, 3, 31, 1, 30));
+        assert_eq!(next_occurrence(Zurich, 2, 30, utc(2024, 3, 31, 1, 30)), utc(2024, 4, 1, 0, 30));
+    }
+
+    #[test]
+    fn reminder_in_autumn_overlap_is_sent_once() {
+        // On 27 October 2024, 03:00 goes back to 02:00: 02:30 exists at 00:30 and 01:30 UTC
+        assert_eq!(occurrence_on(Zurich, 2, 30, date(2024, 10, 27)), utc(2024, 10, 27, 0, 30));
+        assert_eq!(next_occurrence(Zurich, 2, 30, utc(2024, 10, 26, 0, 30)), utc(2024, 10, 27, 0, 30));
+        assert_eq!(next_occurrence(Zurich, 2, 30, utc(2024, 10, 27, 0, 30)), utc(2024, 10, 28, 1, 30));
+    }
+
+    #[test]
+    fn next_occurrence_is_strictly_after() {
+        assert_eq!(next_occurrence(Zurich, 22, 0, utc(2024, 1, 15, 20, 59)), utc(2024, 1, 15, 21, 0));
+        assert_eq!(next_occurrence(Zurich, 22, 0, utc(2024, 1, 15, 21, 0)), utc(2024, 1, 16, 21, 0));
+    }
+
+    #[test]
+    fn nothing_is_due_before_the_next_reminder() {
+        let last = utc(2024, 1, 15, 21, 0);
+        assert!(due_occurrences(Zurich, 22, 0, last, utc(2024, 1, 16, 20, 59)).is_empty());
+        assert_eq!(due_occurrences(Zurich, 22, 0, last, utc(2024, 1, 16, 21, 0)), vec![utc(2024, 1, 16, 21, 0)]);
+    }
+
+    #[test]
+    fn long_downtime_is_caught_up_for_the_last_days() {
+        // Offline for 10 days: only the reminders of the last 7 days are sent, from the oldest
+        let last = utc(2024, 1, 5, 21, 0);
+        let dues = due_occurrences(Zurich, 22, 0, last, utc(2024, 1, 15, 21, 30));
+        let expected: Vec<DateTime<Utc>> = (9..=15).map(|day| utc(2024, 1, day, 21, 0)).collect();
+        assert_eq!(dues.len() as i64, MAX_CATCH_UP_DAYS);
+        assert_eq!(dues, expected);
+    }
+
+    #[test]
+    fn catch_up_keeps_one_reminder_by_local_day_across_dst() {
+        let dues = due_occurrences(Zurich, 2, 30, utc(2024, 3, 29, 1, 30), utc(2024, 4, 2, 12, 0));
+        assert_eq!(
+            dues,
+            vec![utc(2024, 3, 30, 1, 30), utc(2024, 3, 31, 1, 30), utc(2024, 4, 1, 0, 30), utc(2024, 4, 2, 0, 30)]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::future::Future;
+use teloxide::types::ChatId;
+use teloxide::{ApiError, RequestError};
+use tokio::time::{sleep, Duration};
+
+/// Number of tries of a request failing with a temporary error
+const MAX_TRIES: u32 = 4;
+
+/// Time waited after the first network error, doubled after each new error
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+
+/// This function send a request to Telegram and try again after the temporary errors
+///
+/// A request limited by Telegram is sent again after the time asked by Telegram, a request
+/// failing with a network error is sent again after a growing wait.
+///
+/// # Arguments
+/// * `request` - Create the request to send, called for each try
+///
+/// # Return
+/// Return the result of the last try
+pub async fn with_retry<T, F, Fut>(mut request: F) -> Result<T, RequestError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RequestError>>,
+{
+    let mut backoff = FIRST_BACKOFF;
+    let mut tries = 1;
+    loop {
+        let wait = match request().await {
+            Err(RequestError::RetryAfter(wait)) if tries < MAX_TRIES => {
+                log::warn!("Too many requests, try again in {wait:?}");
+                wait
+            }
+            Err(RequestError::Network(e)) if tries < MAX_TRIES => {
+                log::warn!("Network error, try again in {backoff:?}: {e}");
+                let wait = backoff;
+                backoff *= 2;
+                wait
+            }
+            result => return result,
+        };
+        sleep(wait).await;
+        tries += 1;
+    }
+}
+
+/// This function send a request to a chat like `with_retry`, and follow a group which became
+/// a supergroup: the chat is moved to its new id and the request is sent again to it
+///
+/// # Arguments
+/// * `chat_id` - The chat of the request
+/// * `request` - Create the request to send to a chat, called for each try
+/// * `migrate` - Move the chat from its old id to its new id
+///
+/// # Return
+/// Return the result of the last try
+pub async fn to_chat<T, F, Fut, M, MFut>(chat_id: ChatId, mut request: F, migrate: M) -> Result<T, RequestError>
+where
+    F: FnMut(ChatId) -> Fut,
+    Fut: Future<Output = Result<T, RequestError>>,
+    M: FnOnce(ChatId, ChatId) -> MFut,
+    MFut: Future<Output = ()>,
+{
+    match with_retry(|| request(chat_id)).await {
+        Err(RequestError::MigrateToChatId(new_chat_id)) => {
+            let new_chat_id = ChatId(new_chat_id);
+            log::info!("Chat {chat_id} became the supergroup {new_chat_id}");
+            migrate(chat_id, new_chat_id).await;
+            with_retry(|| request(new_chat_id)).await
+        }
+        result => result,
+    }
+}
+
+/// This function check if a request failed because the bot can't write in the chat anymore
+/// (the user blocked the bot or deleted his account, or the bot was removed from the group)
+///
+/// A group which became a supergroup isn't a failure, its messages go to its new id.
+pub fn is_permanent_failure(error: &RequestError) -> bool {
+    matches!(
+        error,
+        RequestError::Api(
+            ApiError::BotBlocked
+                | ApiError::UserDeactivated
+                | ApiError::BotKicked
+                | ApiError::BotKickedFromSupergroup
+                | ApiError::ChatNotFound
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+    use teloxide::requests::{Request, Requester};
+    use teloxide::Bot;
+
+    /// A bot sending its requests to a closed port, they fail with a network error
+    fn offline_bot() -> Bot {
+        Bot::new("1234:token").set_api_url(url::Url::parse("http://127.0.0.1:1").unwrap())
+    }
+
+    #[tokio::test]
+    async fn limited_request_is_sent_again() {
+        let tries = AtomicU32::new(0);
+        let result = with_retry(|| {
+            let try_number = tries.fetch_add(1, Ordering::Relaxed) + 1;
+            async move {
+                match try_number {
+                    1 | 2 => Err(RequestError::RetryAfter(Duration::from_millis(1))),
+                    _ => Ok(try_number),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn tries_are_limited() {
+        let tries = AtomicU32::new(0);
+        let result: Result<(), RequestError> = with_retry(|| {
+            tries.fetch_add(1, Ordering::Relaxed);
+            async { Err(RequestError::RetryAfter(Duration::from_millis(1))) }
+        })
+        .await;
+        assert!(matches!(result, Err(RequestError::RetryAfter(_))));
+        assert_eq!(tries.load(Ordering::Relaxed), MAX_TRIES);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn network_error_is_sent_again_after_a_growing_wait() {
+        let bot = offline_bot();
+        let tries = AtomicU32::new(0);
+        let start = tokio::time::Instant::now();
+        let result = with_retry(|| {
+            let try_number = tries.fetch_add(1, Ordering::Relaxed) + 1;
+            let request = bot.get_me().send();
+            async move {
+                match try_number {
+                    1 | 2 => request.await.map(|_| 0),
+                    _ => Ok(try_number),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+        // 1 second after the first error, 2 seconds after the second
+        assert!(start.elapsed() >= FIRST_BACKOFF * 3);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_sent_again() {
+        let tries = AtomicU32::new(0);
+        let result: Result<(), RequestError> = with_retry(|| {
+            tries.fetch_add(1, Ordering::Relaxed);
+            async { Err(RequestError::Api(ApiError::BotBlocked)) }
+        })
+        .await;
+        assert!(matches!(result, Err(RequestError::Api(ApiError::BotBlocked))));
+        assert_eq!(tries.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn migrated_group_is_followed() {
+        let chats = Mutex::new(vec![]);
+        let migrated = Mutex::new(None);
+        let result = to_chat(
+            ChatId(-100),
+            |chat_id| {
+                chats.lock().unwrap().push(chat_id);
+                async move {
+                    match chat_id {
+                        ChatId(-100) => Err(RequestError::MigrateToChatId(-1001234)),
+                        chat_id => Ok(chat_id),
+                    }
+                }
+            },
+            |old, new| {
+                *migrated.lock().unwrap() = Some((old, new));
+                async {}
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), ChatId(-1001234));
+        assert_eq!(*chats.lock().unwrap(), vec![ChatId(-100), ChatId(-1001234)]);
+        assert_eq!(*migrated.lock().unwrap(), Some((ChatId(-100), ChatId(-1001234))));
+    }
+
+    #[tokio::test]
+    async fn chat_is_kept_without_migration() {
+        let migrated = AtomicU32::new(0);
+        let result = to_chat(
+            ChatId(5),
+            |chat_id| async move { Ok::<_, RequestError>(chat_id) },
+            |_, _| {
+                migrated.fetch_add(1, Ordering::Relaxed);
+                async {}
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), ChatId(5));
+        assert_eq!(migrated.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn failures_are_classified() {
+        let permanent = [ApiError::BotBlocked, ApiError::UserDeactivated, ApiError::BotKicked, ApiError::BotKickedFromSupergroup, ApiError::ChatNotFound];
+        for error in permanent {
+            assert!(is_permanent_failure(&RequestError::Api(error)));
+        }
+        assert!(!is_permanent_failure(&RequestError::MigrateToChatId(-1001234)));
+        assert!(!is_permanent_failure(&RequestError::RetryAfter(Duration::from_secs(3))));
+        assert!(!is_permanent_failure(&RequestError::Api(ApiError::MessageNotModified)));
+        assert!(!is_permanent_failure(&RequestError::Api(ApiError::MessageToEditNotFound)));
+    }
+}
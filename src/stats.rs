@@ -0,0 +1,323 @@
+use crate::scale::Scale;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Period covered by the statistics
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Period {
+    Month,
+    Year,
+    All,
+}
+
+impl Period {
+    /// Parse the argument of /stats, the month by default
+    pub fn parse(text: &str) -> Option<Period> {
+        match text.trim().to_lowercase().as_str() {
+            "" | "month" => Some(Period::Month),
+            "year" => Some(Period::Year),
+            "all" => Some(Period::All),
+            _ => None,
+        }
+    }
+
+    /// First and last date of the period containing `today`
+    ///
+    /// # Arguments
+    /// * `today` - The local date of the user
+    /// * `first` - The first date ranked by the user, start of the period `All`
+    pub fn bounds(&self, today: NaiveDate, first: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Period::Month => (today.with_day(1).unwrap_or(today), today),
+            Period::Year => (today.with_ordinal(1).unwrap_or(today), today),
+            Period::All => (first.min(today), today),
+        }
+    }
+
+    /// First and last date of the whole period before the one containing `today`
+    ///
+    /// # Return
+    /// Return None for the period `All`
+    pub fn previous(&self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match self {
+            Period::Month => {
+                let last = today.with_day(1)? - Duration::days(1);
+                Some((last.with_day(1)?, last))
+            }
+            Period::Year => Some((
+                NaiveDate::from_ymd_opt(today.year() - 1, 1, 1)?,
+                NaiveDate::from_ymd_opt(today.year() - 1, 12, 31)?,
+            )),
+            Period::All => None,
+        }
+    }
+}
+
+/// Last date which can be missed: today once its reminder is sent or it's ranked, else yesterday
+///
+/// # Arguments
+/// * `today` - The local date of the user
+/// * `prompted` - The local date of the last reminder
+/// * `ranked` - The last date ranked
+pub fn last_due(today: NaiveDate, prompted: Option<NaiveDate>, ranked: Option<NaiveDate>) -> NaiveDate {
+    match prompted.max(ranked) {
+        Some(date) if date >= today => today,
+        _ => today - Duration::days(1),
+    }
+}
+
+/// Statistics of the ranks of a period
+pub struct Stats {
+    average_: Option<f64>,
+    distribution_: Vec<(u8, u32)>,
+    rated_: u32,
+    missed_: u32,
+    longest_streak_: u32,
+    best_weekday_: Option<(Weekday, f64)>,
+    worst_weekday_: Option<(Weekday, f64)>,
+}
+
+impl Stats {
+    /// Compute the statistics of a period
+    ///
+    /// The days before the first rank of the user and after `to` or `last_due` aren't counted as missed.
+    ///
+    /// # Arguments
+    /// * `days` - The ranks of the user sorted by date
+    /// * `from` - The first date of the period
+    /// * `to` - The last date of the period
+    /// * `first` - The first date ranked by the user
+    /// * `last_due` - The last date the user could rank, see `last_due`
+    /// * `scale` - The scale of the user, the distribution only counts its ranks
+    pub fn compute(days: &[(NaiveDate, u8)], from: NaiveDate, to: NaiveDate, first: NaiveDate, last_due: NaiveDate, scale: &Scale) -> Stats {
+        let days: Vec<(NaiveDate, u8)> = days
+            .iter()
+            .filter(|(date, _)| *date >= from && *date <= to)
+            .copied()
+            .collect();
+
+        let mut distribution: Vec<(u8, u32)> = scale.get_ranks().into_iter().map(|rank| (rank, 0)).collect();
+        let mut weekdays = [(0u32, 0u32); 7];
+        for (date, rank) in &days {
+            if let Some(position) = scale.position(*rank) {
+                distribution[position].1 += 1;
+            }
+            let weekday = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+            weekday.0 += *rank as u32;
+            weekday.1 += 1;
+        }
+
+        let rated = days.len() as u32;
+        let average = match rated {
+            0 => None,
+            _ => Some(days.iter().map(|(_, rank)| *rank as f64).sum::<f64>() / rated as f64),
+        };
+
+        let start = from.max(first);
+        let end = to.min(last_due);
+        let elapsed = match end >= start {
+            true => (end - start).num_days() as u32 + 1,
+            false => 0,
+        };
+
+        // Longest run of consecutive ranked dates
+        let mut longest_streak = 0;
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for (date, _) in &days {
+            streak = match previous {
+                Some(previous) if *date - previous == Duration::days(1) => streak + 1,
+                _ => 1,
+            };
+            longest_streak = longest_streak.max(streak);
+            previous = Some(*date);
+        }
+
+        let averages: Vec<(Weekday, f64)> = weekdays
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .filter_map(|(i, (sum, count))| {
+                let weekday = Weekday::try_from(i as u8).ok()?;
+                Some((weekday, *sum as f64 / *count as f64))
+            })
+            .collect();
+        let best_weekday = averages.iter().copied().reduce(|a, b| if b.1 > a.1 { b } else { a });
+        let worst_weekday = averages.iter().copied().reduce(|a, b| if b.1 < a.1 { b } else { a });
+
+        Stats {
+            average_: average,
+            distribution_: distribution,
+            rated_: rated,
+            missed_: elapsed.saturating_sub(rated),
+            longest_streak_: longest_streak,
+            best_weekday_: best_weekday,
+            worst_weekday_: worst_weekday,
+        }
+    }
+
+    /// Average rank, None if no day is ranked
+    pub fn get_average(&self) -> Option<f64> {
+        self.average_
+    }
+
+    /// Number of days ranked with each rank of the scale, from the worst rank
+    pub fn get_distribution(&self) -> Vec<(u8, u32)> {
+        self.distribution_.clone()
+    }
+
+    pub fn get_rated(&self) -> u32 {
+        self.rated_
+    }
+
+    pub fn get_missed(&self) -> u32 {
+        self.missed_
+    }
+
+    /// Longest number of consecutive days ranked
+    pub fn get_longest_streak(&self) -> u32 {
+        self.longest_streak_
+    }
+
+    /// Weekday with the best average rank
+    pub fn get_best_weekday(&self) -> Option<(Weekday, f64)> {
+        self.best_weekday_
+    }
+
+    /// Weekday with the worst average rank
+    pub fn get_worst_weekday(&self) -> Option<(Weekday, f64)> {
+        self.worst_weekday_
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn scale() -> Scale {
+        Scale::parse("0-5").unwrap()
+    }
+
+    #[test]
+    fn longest_streak_is_broken_by_a_gap() {
+        // 3 days, a missed day, then 4 days up to the end of the period
+        let days: Vec<(NaiveDate, u8)> = [1, 2, 3, 5, 6, 7, 8].iter().map(|day| (date(2024, 1, *day), 3)).collect();
+        let stats = Stats::compute(&days, date(2024, 1, 1), date(2024, 1, 8), date(2024, 1, 1), date(2024, 1, 8), &scale());
+        assert_eq!(stats.get_longest_streak(), 4);
+        assert_eq!(stats.get_missed(), 1);
+
+        // The first streak is the longest
+        let days: Vec<(NaiveDate, u8)> = [1, 2, 3, 4, 6, 7].iter().map(|day| (date(2024, 1, *day), 3)).collect();
+        let stats = Stats::compute(&days, date(2024, 1, 1), date(2024, 1, 8), date(2024, 1, 1), date(2024, 1, 8), &scale());
+        assert_eq!(stats.get_longest_streak(), 4);
+    }
+
+    #[test]
+    fn streak_continues_across_months() {
+        let days = [(date(2024, 1, 30), 1), (date(2024, 1, 31), 2), (date(2024, 2, 1), 3)];
+        let stats = Stats::compute(&days, date(2024, 1, 1), date(2024, 2, 1), date(2024, 1, 30), date(2024, 2, 1), &scale());
+        assert_eq!(stats.get_longest_streak(), 3);
+    }
+
+    #[test]
+    fn days_before_the_first_rank_are_not_missed() {
+        // The month started on the 1st but the user ranked his first day on the 10th
+        let days: Vec<(NaiveDate, u8)> = (10..=20).filter(|day| day % 4 != 0).map(|day| (date(2024, 1, day), 4)).collect();
+        let stats = Stats::compute(&days, date(2024, 1, 1), date(2024, 1, 20), date(2024, 1, 10), date(2024, 1, 20), &scale());
+        assert_eq!(stats.get_rated(), 8);
+        assert_eq!(stats.get_missed(), 3);
+    }
+
+    #[test]
+    fn days_out_of_the_period_are_ignored() {
+        let days = [(date(2023, 12, 31), 0), (date(2024, 1, 1), 4), (date(2024, 1, 2), 2), (date(2024, 2, 1), 0)];
+        let stats = Stats::compute(&days, date(2024, 1, 1), date(2024, 1, 31), date(2023, 12, 31), date(2024, 1, 31), &scale());
+        assert_eq!(stats.get_rated(), 2);
+        assert_eq!(stats.get_average(), Some(3.0));
+        assert_eq!(stats.get_distribution(), vec![(0, 0), (1, 0), (2, 1), (3, 0), (4, 1), (5, 0)]);
+    }
+
+    #[test]
+    fn no_day_ranked() {
+        let stats = Stats::compute(&[], date(2024, 1, 1), date(2024, 1, 10), date(2024, 1, 1), date(2024, 1, 10), &scale());
+        assert_eq!(stats.get_average(), None);
+        assert_eq!(stats.get_missed(), 10);
+        assert_eq!(stats.get_longest_streak(), 0);
+        assert_eq!(stats.get_best_weekday(), None);
+    }
+
+    #[test]
+    fn weekday_averages() {
+        // 1 January 2024 is a Monday
+        let days = [
+            (date(2024, 1, 1), 1),
+            (date(2024, 1, 8), 2),
+            (date(2024, 1, 5), 5),
+            (date(2024, 1, 12), 4),
+            (date(2024, 1, 3), 3),
+        ];
+        let mut sorted = days.to_vec();
+        sorted.sort();
+        let stats = Stats::compute(&sorted, date(2024, 1, 1), date(2024, 1, 14), date(2024, 1, 1), date(2024, 1, 14), &scale());
+        assert_eq!(stats.get_best_weekday(), Some((Weekday::Fri, 4.5)));
+        assert_eq!(stats.get_worst_weekday(), Some((Weekday::Mon, 1.5)));
+    }
+
+    #[test]
+    fn bounds_end_today() {
+        let today = date(2024, 3, 15);
+        assert_eq!(Period::Month.bounds(today, date(2020, 1, 1)), (date(2024, 3, 1), today));
+        assert_eq!(Period::Year.bounds(today, date(2020, 1, 1)), (date(2024, 1, 1), today));
+        assert_eq!(Period::All.bounds(today, date(2020, 5, 6)), (date(2020, 5, 6), today));
+    }
+
+    #[test]
+    fn previous_month() {
+        assert_eq!(Period::Month.previous(date(2024, 5, 20)), Some((date(2024, 4, 1), date(2024, 4, 30))));
+        // January follows December of the previous year
+        assert_eq!(Period::Month.previous(date(2024, 1, 1)), Some((date(2023, 12, 1), date(2023, 12, 31))));
+        // February of a leap year
+        assert_eq!(Period::Month.previous(date(2024, 3, 31)), Some((date(2024, 2, 1), date(2024, 2, 29))));
+        assert_eq!(Period::Month.previous(date(2023, 3, 1)), Some((date(2023, 2, 1), date(2023, 2, 28))));
+    }
+
+    #[test]
+    fn previous_year() {
+        assert_eq!(Period::Year.previous(date(2024, 1, 1)), Some((date(2023, 1, 1), date(2023, 12, 31))));
+        assert_eq!(Period::Year.previous(date(2025, 2, 28)), Some((date(2024, 1, 1), date(2024, 12, 31))));
+        assert_eq!(Period::All.previous(date(2024, 1, 1)), None);
+    }
+
+    #[test]
+    fn parse_period() {
+        assert_eq!(Period::parse(""), Some(Period::Month));
+        assert_eq!(Period::parse(" Year "), Some(Period::Year));
+        assert_eq!(Period::parse("all"), Some(Period::All));
+        assert_eq!(Period::parse("week"), None);
+    }
+
+    #[test]
+    fn today_is_missed_once_prompted() {
+        let today = date(2024, 1, 10);
+        let yesterday = date(2024, 1, 9);
+        assert_eq!(last_due(today, Some(yesterday), Some(yesterday)), yesterday);
+        assert_eq!(last_due(today, None, None), yesterday);
+        assert_eq!(last_due(today, Some(today), Some(yesterday)), today);
+        assert_eq!(last_due(today, Some(yesterday), Some(today)), today);
+
+        // Ranked up to yesterday: today isn't missed before its reminder
+        let days: Vec<(NaiveDate, u8)> = (1..=9).map(|day| (date(2024, 1, day), 3)).collect();
+        let stats = Stats::compute(&days, date(2024, 1, 1), today, date(2024, 1, 1), yesterday, &scale());
+        assert_eq!(stats.get_missed(), 0);
+        let stats = Stats::compute(&days, date(2024, 1, 1), today, date(2024, 1, 1), today, &scale());
+        assert_eq!(stats.get_missed(), 1);
+
+        // First rank today
+        let stats = Stats::compute(&[(today, 2)], date(2024, 1, 1), today, today, today, &scale());
+        assert_eq!(stats.get_missed(), 0);
+        assert_eq!(stats.get_rated(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::i18n::Language;
+use crate::scale::Scale;
+use crate::user::User;
+use chrono::{DateTime, Utc};
+
+/// Name of the tracker created for a new user
+pub const DEFAULT_TRACKER: &str = "pixel";
+
+/// Biggest number of characters of a tracker name
+pub const MAX_NAME_LENGTH: usize = 20;
+
+/// Something ranked every day by a user, with its own question, scale and reminder
+#[derive(Clone)]
+pub struct Tracker {
+    id_: i64,
+    user_: User,
+    name_: String,
+    question_: String,
+    scale_: Scale,
+    hour_: u8,
+    minute_: u8,
+    nudge_: Option<(u8, u8)>,
+    last_prompt_: Option<DateTime<Utc>>,
+    last_nudge_: Option<DateTime<Utc>>,
+}
+
+impl Tracker {
+    /// Create a tracker not saved yet (its id is 0), its question is in the language of the user
+    ///
+    /// A new tracker gets the default scale and hour of the configuration.
+    pub fn new(user: User, name: String, scale: Scale, hour: u8) -> Tracker {
+        Tracker {
+            id_: 0,
+            question_: Tracker::default_question(&name, user.get_language()),
+            user_: user,
+            name_: name,
+            scale_: scale,
+            hour_: hour,
+            minute_: 0,
+            nudge_: None,
+            last_prompt_: None,
+            last_nudge_: None,
+        }
+    }
+
+    /// Question of a new tracker: the default question for the default tracker, else about its name
+    pub fn default_question(name: &str, language: Language) -> String {
+        match name {
+            DEFAULT_TRACKER => language.text("question-default"),
+            name => language.format("question-tracker", &[("name", &name)]),
+        }
+    }
+
+    /// A name is a word of letters, digits, `-` or `_`, starting with a letter
+    pub fn is_valid_name(name: &str) -> bool {
+        name.chars().next().is_some_and(|c| c.is_alphabetic())
+            && name.chars().count() <= MAX_NAME_LENGTH
+            && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    }
+
+    pub fn get_id(&self) -> i64 {
+        self.id_
+    }
+
+    pub fn set_id(&mut self, id: i64) {
+        self.id_ = id;
+    }
+
+    /// The user owning the tracker
+    pub fn get_user(&self) -> User {
+        self.user_.clone()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name_.clone()
+    }
+
+    /// Question asked to rank a day, followed by the date
+    pub fn get_question(&self) -> String {
+        self.question_.clone()
+    }
+
+    pub fn set_question(&mut self, question: String) {
+        self.question_ = question;
+    }
+
+    /// Ranks proposed to rank a day
+    pub fn get_scale(&self) -> Scale {
+        self.scale_.clone()
+    }
+
+    pub fn get_hour(&self) -> u8 {
+        self.hour_
+    }
+
+    pub fn get_minute(&self) -> u8 {
+        self.minute_
+    }
+
+    /// Time of the reminder formatted as HH:MM
+    pub fn get_time(&self) -> String {
+        format!("{:02}:{:02}", self.hour_, self.minute_)
+    }
+
+    pub fn set_minute(&mut self, minute: u8) {
+        self.minute_ = minute;
+    }
+
+    /// Hour and minute of the second message sent if the day isn't ranked yet
+    pub fn get_nudge(&self) -> Option<(u8, u8)> {
+        self.nudge_
+    }
+
+    pub fn set_nudge(&mut self, nudge: Option<(u8, u8)>) {
+        self.nudge_ = nudge;
+    }
+
+    /// Time of the last reminder sent
+    pub fn get_last_prompt(&self) -> Option<DateTime<Utc>> {
+        self.last_prompt_
+    }
+
+    /// Time of the last nudge sent
+    pub fn get_last_nudge(&self) -> Option<DateTime<Utc>> {
+        self.last_nudge_
+    }
+
+    pub fn set_last_prompt(&mut self, last_prompt: Option<DateTime<Utc>>) {
+        self.last_prompt_ = last_prompt;
+    }
+
+    pub fn set_last_nudge(&mut self, last_nudge: Option<DateTime<Utc>>) {
+        self.last_nudge_ = last_nudge;
+    }
+}
@@ -1,30 +1,165 @@
+use crate::i18n::Language;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
 use teloxide::prelude::ChatId;
+use teloxide::types::{Chat, UserId};
 
 #[derive(Clone)]
 pub struct User {
     chat_id_: ChatId,
-    username_: String,
-    hour_: u8,
+    telegram_id_: Option<UserId>,
+    username_: Option<String>,
+    display_name_: String,
+    timezone_: Tz,
+    language_: Language,
+    paused_until_: Option<NaiveDate>,
+    stopped_: bool,
+    send_failure_: Option<String>,
+    recap_weekly_: bool,
+    recap_monthly_: bool,
 }
 
 impl User {
-    pub fn new(chat_id: ChatId, username: String, hour: Option<u8>) -> User {
+    /// Create a user in a timezone, the default timezone of the configuration for a new user
+    pub fn new(chat_id: ChatId, display_name: String, timezone: Tz) -> User {
         User {
             chat_id_: chat_id,
-            username_: username,
-            hour_: hour.unwrap_or(22),
+            telegram_id_: None,
+            username_: None,
+            display_name_: display_name,
+            timezone_: timezone,
+            language_: Language::default(),
+            paused_until_: None,
+            stopped_: false,
+            send_failure_: None,
+            recap_weekly_: false,
+            recap_monthly_: false,
         }
     }
 
+    /// Create a user from his chat, a group has no username
+    ///
+    /// # Arguments
+    /// * `chat` - The chat of the user
+    /// * `from` - The Telegram user who sent the message, his language is the language of the chat
+    /// * `timezone` - The timezone of the user if he is new
+    pub fn from_chat(chat: &Chat, from: Option<&teloxide::types::User>, timezone: Tz) -> User {
+        let mut user = User::new(chat.id, display_name(chat), timezone);
+        if chat.is_private() {
+            user.set_telegram_id(from.map(|from| from.id));
+            user.set_username(chat.username().map(String::from));
+        }
+        let language = from.and_then(|from| from.language_code.as_deref()).and_then(Language::from_code);
+        user.set_language(language.unwrap_or_default());
+        user
+    }
+
     pub fn get_chat_id(&self) -> ChatId {
         self.chat_id_
     }
 
-    pub fn get_username(&self) -> String {
+    /// Telegram id of the user of a private chat, None for a group
+    pub fn get_telegram_id(&self) -> Option<UserId> {
+        self.telegram_id_
+    }
+
+    pub fn set_telegram_id(&mut self, telegram_id: Option<UserId>) {
+        self.telegram_id_ = telegram_id;
+    }
+
+    /// Username of the user, a user may have none
+    pub fn get_username(&self) -> Option<String> {
         self.username_.clone()
     }
 
-    pub fn get_hour(&self) -> u8 {
-        self.hour_
+    pub fn set_username(&mut self, username: Option<String>) {
+        self.username_ = username;
+    }
+
+    /// Name used in the messages and the pixels
+    pub fn get_display_name(&self) -> String {
+        self.display_name_.clone()
+    }
+
+    pub fn get_timezone(&self) -> Tz {
+        self.timezone_
+    }
+
+    /// Language of the messages sent to the user
+    pub fn get_language(&self) -> Language {
+        self.language_
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language_ = language;
+    }
+
+    /// First local date reminded again after a pause, None if the reminders aren't paused
+    pub fn get_paused_until(&self) -> Option<NaiveDate> {
+        self.paused_until_
+    }
+
+    pub fn set_paused_until(&mut self, paused_until: Option<NaiveDate>) {
+        self.paused_until_ = paused_until;
+    }
+
+    /// True if the user stopped his reminders (or blocked the bot)
+    pub fn is_stopped(&self) -> bool {
+        self.stopped_
+    }
+
+    pub fn set_stopped(&mut self, stopped: bool) {
+        self.stopped_ = stopped;
+    }
+
+    /// Error of the last message which couldn't be sent to the user (ex: he blocked the bot)
+    pub fn get_send_failure(&self) -> Option<String> {
+        self.send_failure_.clone()
+    }
+
+    pub fn set_send_failure(&mut self, send_failure: Option<String>) {
+        self.send_failure_ = send_failure;
+    }
+
+    /// True if the user receives a recap of his week on Sunday
+    pub fn get_recap_weekly(&self) -> bool {
+        self.recap_weekly_
+    }
+
+    pub fn set_recap_weekly(&mut self, recap_weekly: bool) {
+        self.recap_weekly_ = recap_weekly;
+    }
+
+    /// True if the user receives a recap of his month on the 1st
+    pub fn get_recap_monthly(&self) -> bool {
+        self.recap_monthly_
+    }
+
+    pub fn set_recap_monthly(&mut self, recap_monthly: bool) {
+        self.recap_monthly_ = recap_monthly;
+    }
+
+    /// True if the user receives the reminders of a local date
+    pub fn is_reminded(&self, date: NaiveDate) -> bool {
+        !self.stopped_ && self.paused_until_.is_none_or(|paused_until| date >= paused_until)
+    }
+}
+
+/// Name shown for a chat: the username, else the first and last name, or the title of a group
+pub fn display_name(chat: &Chat) -> String {
+    if let Some(title) = chat.title() {
+        return title.to_string();
+    }
+    if let Some(username) = chat.username() {
+        return username.to_string();
+    }
+    let name = [chat.first_name(), chat.last_name()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    match name.is_empty() {
+        true => format!("user {}", chat.id),
+        false => name,
     }
 }
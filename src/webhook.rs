@@ -0,0 +1,275 @@
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::RequestError;
+use teloxide::stop::{mk_stop_token, StopFlag, StopToken};
+use teloxide::update_listeners::{StatefulListener, UpdateListener};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use url::Url;
+
+/// Header of the requests of Telegram with the secret token of the webhook
+const SECRET_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// Biggest number of characters of a secret token (limit of Telegram)
+const MAX_SECRET_LENGTH: usize = 256;
+
+/// Errors of the configuration of the webhook
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The listened address can't be read
+    InvalidAddress(String),
+    /// The public URL can't be read or isn't https
+    InvalidUrl(String),
+    /// The secret token is missing
+    MissingSecret,
+    /// The secret token isn't accepted by Telegram
+    InvalidSecret,
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookError::InvalidAddress(address) => write!(f, "invalid webhook address {address:?}"),
+            WebhookError::InvalidUrl(url) => write!(f, "invalid webhook url {url:?}, an https url is needed"),
+            WebhookError::MissingSecret => write!(f, "a webhook secret ($WEBHOOK_SECRET) is needed with the webhook url"),
+            WebhookError::InvalidSecret => write!(
+                f,
+                "invalid webhook secret, use 1 to {MAX_SECRET_LENGTH} characters among a-z, A-Z, 0-9, _ and -"
+            ),
+        }
+    }
+}
+
+impl Error for WebhookError {}
+
+/// Where Telegram sends the updates when the bot uses a webhook
+#[derive(Clone)]
+pub struct WebhookConfig {
+    /// Address listened by the bot (ex: behind a reverse proxy)
+    pub address: SocketAddr,
+    /// Public URL given to Telegram, its path is the path listened
+    pub url: Url,
+    /// Token sent by Telegram in each request, the other requests are refused
+    pub secret: String,
+}
+
+impl WebhookConfig {
+    /// Create the webhook after checking its values
+    pub fn new(address: &str, url: &str, secret: String) -> Result<WebhookConfig, WebhookError> {
+        let address = address
+            .parse::<SocketAddr>()
+            .map_err(|_| WebhookError::InvalidAddress(address.to_string()))?;
+        let url = Url::parse(url)
+            .ok()
+            .filter(|parsed| parsed.scheme() == "https")
+            .ok_or_else(|| WebhookError::InvalidUrl(url.to_string()))?;
+        let valid_secret = (1..=MAX_SECRET_LENGTH).contains(&secret.len())
+            && secret.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_secret {
+            return Err(WebhookError::InvalidSecret);
+        }
+        Ok(WebhookConfig { address, url, secret })
+    }
+}
+
+/// State shared by the requests received by the webhook
+struct WebhookState {
+    path: String,
+    secret: String,
+    tx: mpsc::UnboundedSender<Result<Update, Infallible>>,
+    flag: StopFlag,
+}
+
+/// This function start the server receiving the updates of the webhook
+///
+/// The server checks the path and the secret token of each request and gives the updates
+/// to the listener returned. It stops with the listener (ex: with Ctrl-C).
+///
+/// # Arguments
+/// * `config` - The webhook
+///
+/// # Return
+/// Return the address listened and the listener to give to the dispatcher, an error if the
+/// address can't be listened
+pub fn serve(config: &WebhookConfig) -> Result<(SocketAddr, impl UpdateListener<Err = Infallible>), hyper::Error> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let (stop_token, stop_flag) = mk_stop_token();
+    let state = Arc::new(WebhookState {
+        path: config.url.path().to_string(),
+        secret: config.secret.clone(),
+        tx,
+        flag: stop_flag.clone(),
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle_request(state.clone(), request))) }
+    });
+    let server = Server::try_bind(&config.address)?.serve(make_service);
+    let address = server.local_addr();
+
+    tokio::spawn(async move {
+        if let Err(e) = server.with_graceful_shutdown(stop_flag).await {
+            log::error!("Webhook server failed: {e}");
+        }
+    });
+
+    let stream = UnboundedReceiverStream::new(rx);
+    let listener = StatefulListener::new((stream, stop_token), stream_of, |state: &mut (_, StopToken)| state.1.clone());
+    Ok((address, listener))
+}
+
+/// This function give the webhook to Telegram, call `serve` first so the updates sent at
+/// once are received
+///
+/// # Arguments
+/// * `bot` - The bot
+/// * `config` - The webhook
+///
+/// # Return
+/// Return an error if Telegram refuses the webhook
+pub async fn register(bot: &Bot, config: &WebhookConfig) -> Result<(), RequestError> {
+    bot.set_webhook(config.url.clone())
+        .secret_token(config.secret.clone())
+        .await?;
+    Ok(())
+}
+
+/// This function delete the webhook, so the bot can be started again with long polling
+pub async fn unregister(bot: &Bot) -> Result<(), RequestError> {
+    bot.delete_webhook().await?;
+    Ok(())
+}
+
+/// The stream of updates of the state of the listener
+fn stream_of<S>(state: &mut (S, StopToken)) -> &mut S {
+    &mut state.0
+}
+
+/// This function read an update sent by Telegram and give it to the dispatcher
+///
+/// # Arguments
+/// * `state` - The state of the webhook
+/// * `request` - The request received
+///
+/// # Return
+/// Return the response to Telegram: 200 if the update is received
+async fn handle_request(state: Arc<WebhookState>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let status = match receive_update(&state, request).await {
+        Ok(()) => StatusCode::OK,
+        Err(status) => status,
+    };
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    Ok(response)
+}
+
+/// Read the update of a request, return the status of the error if it isn't accepted
+async fn receive_update(state: &WebhookState, request: Request<Body>) -> Result<(), StatusCode> {
+    if request.method() != Method::POST || request.uri().path() != state.path {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let secret = request.headers().get(SECRET_HEADER).map(|secret| secret.as_bytes());
+    if !secret.is_some_and(|secret| same_secret(secret, state.secret.as_bytes())) {
+        log::warn!("Request on the webhook without the secret token");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if state.flag.is_stopped() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let body = hyper::body::to_bytes(request.into_body()).await.map_err(|e| {
+        log::error!("Failed to read a request of the webhook: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    let update = serde_json::from_slice::<Update>(&body).map_err(|e| {
+        log::error!("Failed to read an update of the webhook: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    state.tx.send(Ok(update)).map_err(|_| StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// Compare the secret tokens in a time not depending on the first different character
+fn same_secret(received: &[u8], expected: &[u8]) -> bool {
+    received.len() == expected.len()
+        && received.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Client;
+    use teloxide::update_listeners::AsUpdateStream;
+    use tokio_stream::StreamExt;
+
+    const UPDATE: &str = r#"{"update_id":42,"message":{"message_id":1,"date":1700000000,
+        "chat":{"id":5,"type":"private","first_name":"Ann"},
+        "from":{"id":5,"is_bot":false,"first_name":"Ann"},"text":"/start"}}"#;
+
+    fn config() -> WebhookConfig {
+        WebhookConfig::new("127.0.0.1:0", "https://bot.example.com/telegram", "s3cret".to_string()).unwrap()
+    }
+
+    async fn post(address: SocketAddr, path: &str, secret: Option<&str>, body: &str) -> StatusCode {
+        let mut request = Request::post(format!("http://{address}{path}")).header("Content-Type", "application/json");
+        if let Some(secret) = secret {
+            request = request.header(SECRET_HEADER, secret);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        Client::new().request(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn update_with_secret_is_received() {
+        let (address, mut listener) = serve(&config()).unwrap();
+        assert_eq!(post(address, "/telegram", Some("s3cret"), UPDATE).await, StatusCode::OK);
+
+        let stream = listener.as_stream();
+        tokio::pin!(stream);
+        let update = stream.next().await.unwrap().unwrap();
+        assert_eq!(update.id, 42);
+    }
+
+    #[tokio::test]
+    async fn update_without_secret_is_refused() {
+        let (address, _listener) = serve(&config()).unwrap();
+        assert_eq!(post(address, "/telegram", None, UPDATE).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post(address, "/telegram", Some("wrong"), UPDATE).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post(address, "/telegram", Some("s3cret!"), UPDATE).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn other_path_is_not_found() {
+        let (address, _listener) = serve(&config()).unwrap();
+        assert_eq!(post(address, "/other", Some("s3cret"), UPDATE).await, StatusCode::NOT_FOUND);
+        assert_eq!(post(address, "/", Some("s3cret"), UPDATE).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn invalid_update_is_bad_request() {
+        let (address, _listener) = serve(&config()).unwrap();
+        assert_eq!(post(address, "/telegram", Some("s3cret"), "{}").await, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn config_is_checked() {
+        assert_eq!(
+            WebhookConfig::new("localhost", "https://bot.example.com", "s".to_string()).err(),
+            Some(WebhookError::InvalidAddress("localhost".to_string()))
+        );
+        assert_eq!(
+            WebhookConfig::new("0.0.0.0:8443", "http://bot.example.com", "s".to_string()).err(),
+            Some(WebhookError::InvalidUrl("http://bot.example.com".to_string()))
+        );
+        assert_eq!(
+            WebhookConfig::new("0.0.0.0:8443", "https://bot.example.com", "a b".to_string()).err(),
+            Some(WebhookError::InvalidSecret)
+        );
+    }
+}